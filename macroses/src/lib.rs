@@ -0,0 +1,46 @@
+use proc_macro::TokenStream;
+use quote::quote;
+use syn::{parse_macro_input, Data, DeriveInput, Fields};
+
+/// For enums with unit variants: `as_array()` lists every variant in the
+/// declaration order, `as_str()` gives the name of the variant.
+#[proc_macro_derive(as_array)]
+pub fn as_array(input: TokenStream) -> TokenStream {
+    let input = parse_macro_input!(input as DeriveInput);
+    let name = &input.ident;
+    let variants = match &input.data {
+        Data::Enum(data) => &data.variants,
+        _ => {
+            return syn::Error::new_spanned(&input.ident, "as_array supports only enums")
+                .to_compile_error()
+                .into()
+        }
+    };
+    if let Some(variant) = variants
+        .iter()
+        .find(|variant| !matches!(variant.fields, Fields::Unit))
+    {
+        return syn::Error::new_spanned(variant, "as_array supports only unit variants")
+            .to_compile_error()
+            .into();
+    }
+    let idents = variants
+        .iter()
+        .map(|variant| &variant.ident)
+        .collect::<Vec<_>>();
+    let names = idents.iter().map(|ident| ident.to_string());
+    quote! {
+        impl #name {
+            pub fn as_array() -> &'static [#name] {
+                &[#(#name::#idents),*]
+            }
+
+            pub fn as_str(&self) -> &'static str {
+                match self {
+                    #(#name::#idents => #names),*
+                }
+            }
+        }
+    }
+    .into()
+}
@@ -0,0 +1,236 @@
+//! Snapshots thecocktaildb.com into a local dataset.
+//!
+//! Usage: `import <dataset.json>`
+//! The base url and the api key are taken from `COCKTAIL_API_URL` and `COCKTAIL_API_KEY`.
+//! Every finished request is appended to `<dataset.json>.progress`, so an interrupted
+//! import continues from the last one. The diff against the previous snapshot is
+//! written to `<dataset.json>.diff.txt`.
+use std::collections::HashSet;
+use std::fs::{File, OpenOptions};
+use std::io::{BufRead, BufReader, Write};
+use std::path::Path;
+
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+use bar_bot::cocktails_api::schemas::dataset::Dataset;
+use bar_bot::cocktails_api::schemas::drink::{Drink, LangDrink};
+use bar_bot::cocktails_api::schemas::ingredient::Ingredient;
+use bar_bot::cocktails_api::schemas::lists::List;
+use bar_bot::cocktails_api::services::http_source::{
+    HttpSource, ALL_CATEGORY, ALL_GLASSES, ALL_INGREDIENTS, DEFAULT_API_KEY, DEFAULT_BASE_URL,
+    INGREDIENT_BY_NAME, SEARCH_BY_FIRST_LATTER,
+};
+use bar_bot::error::error_handler::{ErrorHandler, ErrorType};
+
+/// One line of the progress file.
+#[derive(Serialize, Deserialize)]
+enum Step {
+    Letter(char, Vec<Drink>),
+    Lists {
+        ingredients: Vec<String>,
+        categories: Vec<String>,
+        glasses: Vec<String>,
+    },
+    Ingredient(String, Option<Ingredient>),
+}
+
+#[derive(Default)]
+struct Progress {
+    letters: Vec<char>,
+    drinks: Vec<Drink>,
+    ingredient_names: Option<Vec<String>>,
+    categories: Option<Vec<String>>,
+    glasses: Option<Vec<String>>,
+    ingredients_done: HashSet<String>,
+    ingredients: Vec<Ingredient>,
+}
+
+impl Progress {
+    /// Replays the progress file, a line cut by an interruption is dropped.
+    fn read(path: &str) -> Result<Progress, ErrorHandler> {
+        let mut progress = Progress::default();
+        if !Path::new(path).exists() {
+            return Ok(progress);
+        }
+        let file = File::open(path).map_err(file_error)?;
+        for line in BufReader::new(file).lines() {
+            match serde_json::from_str::<Step>(&line.map_err(file_error)?) {
+                Ok(step) => progress.apply(step),
+                Err(error) => log::warn!("Skip a broken progress line: {}", error),
+            }
+        }
+        Ok(progress)
+    }
+
+    fn apply(&mut self, step: Step) {
+        match step {
+            Step::Letter(letter, drinks) => {
+                self.letters.push(letter);
+                self.drinks.extend(drinks);
+            }
+            Step::Lists {
+                ingredients,
+                categories,
+                glasses,
+            } => {
+                self.ingredient_names = Some(ingredients);
+                self.categories = Some(categories);
+                self.glasses = Some(glasses);
+            }
+            Step::Ingredient(name, ingredient) => {
+                self.ingredients_done.insert(name);
+                self.ingredients.extend(ingredient);
+            }
+        }
+    }
+
+    /// Appends the step to the file, then applies it.
+    fn save(&mut self, file: &mut File, step: Step) -> Result<(), ErrorHandler> {
+        let mut line = serde_json::to_vec(&step)?;
+        line.push(b'\n');
+        file.write_all(&line).map_err(file_error)?;
+        self.apply(step);
+        Ok(())
+    }
+}
+
+#[tokio::main]
+async fn main() {
+    let _ = dotenv::dotenv();
+    pretty_env_logger::init();
+
+    let output = match std::env::args().nth(1) {
+        Some(output) => output,
+        None => {
+            eprintln!("Usage: import <dataset.json>");
+            std::process::exit(2);
+        }
+    };
+    if let Err(error) = run(&output).await {
+        log::error!("Import failed: {:?}", error);
+        std::process::exit(1);
+    }
+}
+
+async fn run(output: &str) -> Result<(), ErrorHandler> {
+    let source = HttpSource::new(
+        &std::env::var("COCKTAIL_API_URL").unwrap_or_else(|_| DEFAULT_BASE_URL.to_string()),
+        &std::env::var("COCKTAIL_API_KEY").unwrap_or_else(|_| DEFAULT_API_KEY.to_string()),
+    );
+    let progress_path = format!("{}.progress", output);
+    let mut progress = Progress::read(&progress_path)?;
+    let mut progress_file = OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(&progress_path)
+        .map_err(file_error)?;
+    let previous: Dataset = read_json(output)?.unwrap_or_default();
+
+    for letter in ('a'..='z').chain('0'..='9') {
+        if progress.letters.contains(&letter) {
+            continue;
+        }
+        let values = source
+            .fetch::<Value>(SEARCH_BY_FIRST_LATTER, Some(&letter.to_string()))
+            .await?
+            .unwrap_or_default();
+        let mut drinks = vec![];
+        for value in values.iter() {
+            match LangDrink::drink_from_value(value) {
+                Ok(drink) => drinks.push(drink),
+                Err(error) => log::warn!("Skip drink on '{}': {}", letter, error.msg),
+            }
+        }
+        progress.save(&mut progress_file, Step::Letter(letter, drinks))?;
+        log::info!("Letter '{}': {} drinks.", letter, values.len());
+    }
+
+    if progress.ingredient_names.is_none() {
+        let step = Step::Lists {
+            ingredients: fetch_list(&source, ALL_INGREDIENTS).await?,
+            categories: fetch_list(&source, ALL_CATEGORY).await?,
+            glasses: fetch_list(&source, ALL_GLASSES).await?,
+        };
+        progress.save(&mut progress_file, step)?;
+    }
+
+    for name in progress.ingredient_names.clone().unwrap_or_default() {
+        if progress.ingredients_done.contains(&name) {
+            continue;
+        }
+        let ingredient = match source
+            .fetch::<Ingredient>(INGREDIENT_BY_NAME, Some(&name))
+            .await?
+        {
+            Some(mut found) if !found.is_empty() => Some(found.remove(0)),
+            _ => {
+                log::warn!("Ingredient '{}' not found.", name);
+                None
+            }
+        };
+        progress.save(&mut progress_file, Step::Ingredient(name, ingredient))?;
+    }
+    drop(progress_file);
+
+    let mut seen = HashSet::new();
+    let mut drinks = progress
+        .drinks
+        .into_iter()
+        .filter(|drink| seen.insert(drink.key().to_string()))
+        .collect::<Vec<Drink>>();
+    drinks.sort_by(|first, second| first.name.cmp(&second.name));
+
+    let dataset = Dataset {
+        version: previous.version + 1,
+        drinks,
+        ingredients: progress.ingredients,
+        categories: progress.categories.unwrap_or_default(),
+        glasses: progress.glasses.unwrap_or_default(),
+    };
+    let diff = dataset.diff(&previous);
+
+    let tmp_path = format!("{}.tmp", output);
+    write_json(&tmp_path, &dataset)?;
+    std::fs::rename(&tmp_path, output).map_err(file_error)?;
+    std::fs::write(format!("{}.diff.txt", output), diff.to_string()).map_err(file_error)?;
+    std::fs::remove_file(&progress_path).map_err(file_error)?;
+
+    println!("{}", diff);
+    log::info!(
+        "Dataset v{} saved: {} drinks, {} ingredients.",
+        dataset.version,
+        dataset.drinks.len(),
+        dataset.ingredients.len()
+    );
+    Ok(())
+}
+
+async fn fetch_list(source: &HttpSource, endpoint: &str) -> Result<Vec<String>, ErrorHandler> {
+    Ok(source
+        .fetch::<List>(endpoint, None)
+        .await?
+        .unwrap_or_default()
+        .into_iter()
+        .map(|list| list.name)
+        .collect())
+}
+
+fn read_json<T: serde::de::DeserializeOwned>(path: &str) -> Result<Option<T>, ErrorHandler> {
+    if !Path::new(path).exists() {
+        return Ok(None);
+    }
+    let bytes = std::fs::read(path).map_err(file_error)?;
+    Ok(Some(serde_json::from_slice::<T>(&bytes)?))
+}
+
+fn write_json<T: Serialize>(path: &str, value: &T) -> Result<(), ErrorHandler> {
+    std::fs::write(path, serde_json::to_vec(value)?).map_err(file_error)
+}
+
+fn file_error(error: std::io::Error) -> ErrorHandler {
+    ErrorHandler {
+        msg: error.to_string(),
+        ty: ErrorType::File,
+    }
+}
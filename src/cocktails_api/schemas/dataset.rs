@@ -0,0 +1,136 @@
+use std::collections::HashMap;
+use std::fmt::{Display, Formatter};
+
+use serde::{Deserialize, Serialize};
+
+use crate::cocktails_api::schemas::drink::Drink;
+use crate::cocktails_api::schemas::ingredient::Ingredient;
+
+/// Local snapshot of the cocktail database.
+#[derive(Deserialize, Serialize, Debug, Default)]
+pub struct Dataset {
+    pub version: u32,
+    pub drinks: Vec<Drink>,
+    pub ingredients: Vec<Ingredient>,
+    pub categories: Vec<String>,
+    pub glasses: Vec<String>,
+}
+
+impl Dataset {
+    /// Compares drinks by id with an older snapshot, the drinks without one by name.
+    pub fn diff(&self, previous: &Dataset) -> DatasetDiff {
+        let old = previous
+            .drinks
+            .iter()
+            .map(|drink| (drink.key(), drink))
+            .collect::<HashMap<&str, &Drink>>();
+        let new = self
+            .drinks
+            .iter()
+            .map(|drink| (drink.key(), drink))
+            .collect::<HashMap<&str, &Drink>>();
+
+        let mut diff = DatasetDiff {
+            from_version: previous.version,
+            to_version: self.version,
+            ..DatasetDiff::default()
+        };
+        for (key, drink) in new.iter() {
+            match old.get(key) {
+                None => diff.added.push(drink.name.clone()),
+                Some(old_drink) if old_drink != drink => diff.changed.push(drink.name.clone()),
+                Some(_) => {}
+            }
+        }
+        diff.removed = old
+            .iter()
+            .filter(|(key, _)| !new.contains_key(*key))
+            .map(|(_, drink)| drink.name.clone())
+            .collect();
+        diff.added.sort();
+        diff.changed.sort();
+        diff.removed.sort();
+        diff
+    }
+}
+
+#[derive(Debug, Default)]
+pub struct DatasetDiff {
+    pub from_version: u32,
+    pub to_version: u32,
+    pub added: Vec<String>,
+    pub removed: Vec<String>,
+    pub changed: Vec<String>,
+}
+
+impl DatasetDiff {
+    pub fn is_empty(&self) -> bool {
+        self.added.is_empty() && self.removed.is_empty() && self.changed.is_empty()
+    }
+}
+
+impl Display for DatasetDiff {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        writeln!(f, "Dataset v{} -> v{}", self.from_version, self.to_version)?;
+        for (title, names) in [
+            ("Added", &self.added),
+            ("Removed", &self.removed),
+            ("Changed", &self.changed),
+        ] {
+            writeln!(f, "{} ({}):", title, names.len())?;
+            for name in names {
+                writeln!(f, " - {}", name)?;
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::cocktails_api::schemas::dataset::Dataset;
+    use crate::cocktails_api::schemas::drink::Drink;
+    use crate::utils::testing::DrinkBuilder;
+
+    fn drink(name: &str, glass: &str) -> Drink {
+        DrinkBuilder::new(name).glass(glass).build()
+    }
+
+    #[test]
+    fn test_diff() {
+        let previous = Dataset {
+            version: 1,
+            drinks: vec![
+                drink("Margarita", "Cocktail glass"),
+                drink("Mojito", "Highball glass"),
+            ],
+            ..Dataset::default()
+        };
+        let current = Dataset {
+            version: 2,
+            drinks: vec![
+                drink("Margarita", "Coupe"),
+                drink("Negroni", "Old-fashioned glass"),
+            ],
+            ..Dataset::default()
+        };
+        let diff = current.diff(&previous);
+        assert_eq!(diff.added, vec!["Negroni"]);
+        assert_eq!(diff.removed, vec!["Mojito"]);
+        assert_eq!(diff.changed, vec!["Margarita"]);
+    }
+
+    #[test]
+    fn test_diff_by_id() {
+        let margarita = |id: &str| DrinkBuilder::new("Margarita").id(id).build();
+        let previous = Dataset {
+            drinks: vec![margarita("1")],
+            ..Dataset::default()
+        };
+        let current = Dataset {
+            drinks: vec![margarita("1"), margarita("2")],
+            ..Dataset::default()
+        };
+        assert_eq!(current.diff(&previous).added, vec!["Margarita"]);
+    }
+}
@@ -1,16 +1,20 @@
+use std::collections::BTreeMap;
 use std::fmt::{Display, Formatter};
 use std::sync::Arc;
 
 use serde::{Deserialize, Serialize};
 use serde_json::Value;
 
+use crate::cocktails_api::schemas::measure::{Measure, Units};
 use crate::cocktails_api::schemas::ToLangDrink;
 use crate::error::error_handler::ErrorHandler;
 use crate::error::error_handler::ErrorType;
+use crate::localization;
 use crate::localization::lang::Lang;
 use crate::utils::str_builder::StringBuilder;
 use crate::utils::unicod::Emojis;
 
+pub const ID: &str = "idDrink";
 pub const MEASURE: &str = "strMeasure";
 pub const INSTRUCTIONS: &str = "strInstructions";
 pub const NAME: &str = "strDrink";
@@ -25,8 +29,10 @@ pub trait WithPhoto {
     fn get_url(&self) -> Option<String>;
 }
 
-#[derive(Deserialize, Debug)]
+#[derive(Deserialize, Serialize, Debug, Clone)]
 pub struct LazyDrink {
+    #[serde(rename = "idDrink")]
+    pub id: String,
     #[serde(rename = "strDrink")]
     pub name: String,
     #[serde(rename = "strDrinkThumb")]
@@ -66,25 +72,38 @@ impl Display for LangLazyDrink {
     }
 }
 
-#[derive(Deserialize, Serialize, Debug, Clone)]
+#[derive(Deserialize, Serialize, Debug, Clone, PartialEq)]
 pub struct Drink {
+    #[serde(default)]
+    pub id: Option<String>,
     pub name: String,
     pub ty: Option<String>,
     pub category: Option<String>,
     pub alco: bool,
     pub glass: Option<String>,
     pub instructions: Option<String>,
+    /// Instructions in other languages by lowercase locale code, like `de` or `zh-hans`.
+    #[serde(default)]
+    pub localized_instructions: BTreeMap<String, String>,
     pub image: Option<String>,
     pub ingredients: Vec<(String, Option<String>)>,
 }
 
-impl ToLangDrink<Value> for LangDrink {
+impl Drink {
+    /// Unique within a dataset, names repeat.
+    pub fn key(&self) -> &str {
+        self.id.as_deref().unwrap_or(&self.name)
+    }
+}
+
+impl ToLangDrink<Drink> for LangDrink {
     type Output = Drink;
 
-    fn new(drink: Value, lang: Arc<Lang>) -> Result<Self, ErrorHandler> {
+    fn new(drink: Drink, lang: Arc<Lang>) -> Result<Self, ErrorHandler> {
         Ok(Self {
-            drink: Self::drink_from_value(&drink)?,
+            drink,
             lang,
+            units: Units::default(),
         })
     }
     fn get_drink(&self) -> &Drink {
@@ -96,18 +115,19 @@ impl ToLangDrink<Value> for LangDrink {
 pub struct LangDrink {
     pub drink: Drink,
     pub lang: Arc<Lang>,
+    #[serde(default)]
+    pub units: Units,
 }
 
 impl LangDrink {
-    pub fn new(value: Value, lang: Arc<Lang>) -> Result<Self, ErrorHandler> {
-        Ok(Self {
-            drink: Self::drink_from_value(&value)?,
-            lang,
-        })
-    }
-
-    fn drink_from_value(input: &Value) -> Result<Drink, ErrorHandler> {
+    pub fn drink_from_value(input: &Value) -> Result<Drink, ErrorHandler> {
         Ok(Drink {
+            id: {
+                match input.get(ID.to_owned()) {
+                    Some(value) => serde_json::from_value::<Option<String>>(value.clone())?,
+                    None => None,
+                }
+            },
             name: {
                 match input.get(NAME.to_owned()) {
                     Some(value) => serde_json::from_value::<String>(value.clone())?,
@@ -147,6 +167,24 @@ impl LangDrink {
                     None => None,
                 }
             },
+            localized_instructions: {
+                let mut map = BTreeMap::new();
+                if let Some(fields) = input.as_object() {
+                    for (key, value) in fields {
+                        let code = match key.strip_prefix(INSTRUCTIONS) {
+                            Some(code) if !code.is_empty() => code.to_lowercase(),
+                            _ => continue,
+                        };
+                        match serde_json::from_value::<Option<String>>(value.clone())? {
+                            Some(text) if !text.trim().is_empty() => {
+                                map.insert(code, text);
+                            }
+                            _ => {}
+                        }
+                    }
+                }
+                map
+            },
             image: {
                 match input.get(IMAGE.to_owned()) {
                     Some(value) => serde_json::from_value::<Option<String>>(value.clone())?,
@@ -157,12 +195,11 @@ impl LangDrink {
                 let mut vec: Vec<(String, Option<String>)> = vec![];
                 let mut counter = 1;
 
-                while let Some(ingr) = serde_json::from_value::<Option<String>>(
-                    input
-                        .get(format!("{}{}", INGREDIENT, counter))
-                        .unwrap()
-                        .clone(),
-                )? {
+                while let Some(value) = input.get(format!("{}{}", INGREDIENT, counter)) {
+                    let ingr = match serde_json::from_value::<Option<String>>(value.clone())? {
+                        Some(ingr) => ingr,
+                        None => break,
+                    };
                     let measure = match input.get(format!("{}{}", MEASURE, counter)) {
                         Some(value) => serde_json::from_value::<Option<String>>(value.clone())?,
                         None => None,
@@ -177,6 +214,30 @@ impl LangDrink {
     }
 }
 
+impl LangDrink {
+    pub fn in_units(mut self, units: Units) -> Self {
+        self.units = units;
+        self
+    }
+
+    /// Instructions in the user's language, then in the languages of its fallback
+    /// chain, then in English. Each step checks the source and the offline table.
+    pub fn instructions(&self) -> Option<String> {
+        let drink = &self.drink;
+        std::iter::once(self.lang.code())
+            .chain(self.lang.instructions_fallback.iter().map(String::as_str))
+            .find_map(|code| {
+                drink.localized_instructions.get(code).cloned().or_else(|| {
+                    drink
+                        .id
+                        .as_deref()
+                        .and_then(|id| localization::offline_instructions(code, id))
+                })
+            })
+            .or_else(|| drink.instructions.clone())
+    }
+}
+
 impl WithPhoto for LangDrink {
     fn get_url(&self) -> Option<String> {
         self.get_drink().image.as_ref().cloned()
@@ -187,6 +248,19 @@ impl Display for LangDrink {
     fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
         let result = Emojis::Drink.random().unwrap_or(&' ');
         let drink = self.get_drink();
+        let names = &self.lang.names;
+        let ingredients = drink
+            .ingredients
+            .iter()
+            .map(|(name, measure)| {
+                (
+                    names.ingredient(name),
+                    measure
+                        .as_deref()
+                        .map(|measure| Measure::convert_text(measure, self.units)),
+                )
+            })
+            .collect::<Vec<(String, Option<String>)>>();
         let str_builder = StringBuilder::new()
             .add(
                 &format!(
@@ -201,7 +275,10 @@ impl Display for LangDrink {
             )
             .add(
                 &format!("{}: ", &self.lang.service_responses.category),
-                drink.category.clone(),
+                drink
+                    .category
+                    .as_deref()
+                    .map(|category| names.category(category)),
             )
             .add(
                 &format!("{}: ", &self.lang.service_responses.alco),
@@ -212,14 +289,49 @@ impl Display for LangDrink {
             )
             .add(
                 &format!("{}: ", self.lang.service_responses.glass),
-                drink.glass.clone(),
+                drink.glass.as_deref().map(|glass| names.glass(glass)),
             )
             .add(
                 &format!("{}: ", self.lang.service_responses.cook),
-                drink.instructions.clone(),
+                self.instructions(),
             )
-            .add_many(&drink.ingredients);
+            .add_many(&ingredients);
 
         write!(f, "{}", str_builder.get_str())
     }
 }
+
+#[cfg(test)]
+mod test {
+    use std::sync::Arc;
+
+    use serde_json::json;
+
+    use crate::cocktails_api::schemas::drink::LangDrink;
+    use crate::cocktails_api::schemas::measure::Units;
+    use crate::localization::lang::Lang;
+
+    #[test]
+    fn test_localized_instructions() {
+        let value = json!({
+            "strDrink": "Margarita",
+            "strInstructions": "Shake.",
+            "strInstructionsDE": "Schütteln.",
+            "strInstructionsIT": null,
+            "strInstructionsZH-HANS": ""
+        });
+        let drink = LangDrink::drink_from_value(&value).unwrap();
+        assert_eq!(drink.localized_instructions.len(), 1);
+
+        let instructions = |code: &str| {
+            LangDrink {
+                drink: drink.clone(),
+                lang: Arc::new(Lang::new(code)),
+                units: Units::Original,
+            }
+            .instructions()
+        };
+        assert_eq!(instructions("de"), Some("Schütteln.".to_string()));
+        assert_eq!(instructions("uk"), Some("Shake.".to_string()));
+    }
+}
@@ -1,14 +1,14 @@
 use std::fmt::{Display, Formatter};
 use std::sync::Arc;
 
-use serde::{de, Deserialize};
+use serde::{de, Deserialize, Serialize, Serializer};
 
 use crate::cocktails_api::schemas::ToLangDrink;
 use crate::localization::lang::Lang;
 use crate::utils::str_builder::StringBuilder;
 use crate::ErrorHandler;
 
-#[derive(Deserialize, Debug)]
+#[derive(Deserialize, Serialize, Debug, Clone)]
 pub struct Ingredient {
     #[serde(rename = "strIngredient")]
     pub name: String,
@@ -16,7 +16,11 @@ pub struct Ingredient {
     pub description: Option<String>,
     #[serde(rename = "strType")]
     pub ty: Option<String>,
-    #[serde(rename = "strAlcohol", deserialize_with = "deserialize_bool")]
+    #[serde(
+        rename = "strAlcohol",
+        deserialize_with = "deserialize_bool",
+        serialize_with = "serialize_bool"
+    )]
     pub alco: bool,
 }
 
@@ -43,7 +47,7 @@ impl Display for LangIngredient {
         let result = StringBuilder::new()
             .add(
                 &format!("{}: ", self.lang.service_responses.ingredient_name),
-                Some(self.ingredient.name.clone()),
+                Some(self.lang.names.ingredient(&self.ingredient.name)),
             )
             .add(
                 &format!("{}: ", self.lang.service_responses.description),
@@ -80,3 +84,13 @@ where
         _ => Err(de::Error::unknown_variant(s, &["Yes", "No"])),
     }
 }
+
+fn serialize_bool<S>(value: &bool, serializer: S) -> Result<S::Ok, S::Error>
+where
+    S: Serializer,
+{
+    serializer.serialize_str(match value {
+        true => "Yes",
+        false => "No",
+    })
+}
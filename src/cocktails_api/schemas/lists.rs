@@ -1,13 +1,13 @@
 use std::fmt::{Display, Formatter};
 use std::sync::Arc;
 
-use serde::Deserialize;
+use serde::{Deserialize, Serialize};
 
 use crate::cocktails_api::schemas::ToLangDrink;
 use crate::localization::lang::Lang;
 use crate::ErrorHandler;
 
-#[derive(Deserialize)]
+#[derive(Deserialize, Serialize, Clone, Debug)]
 pub struct List {
     #[serde(alias = "strCategory", alias = "strGlass", alias = "strIngredient1")]
     pub name: String,
@@ -33,7 +33,8 @@ impl Display for LangList {
         write!(
             f,
             " - {} {}",
-            self.lang.service_responses.beverage_name, self.list.name
+            self.lang.service_responses.beverage_name,
+            self.lang.names.any(&self.list.name)
         )
     }
 }
@@ -0,0 +1,128 @@
+use std::fmt::{Display, Formatter};
+use std::sync::Arc;
+
+use crate::cocktails_api::schemas::drink::{Drink, LangDrink, WithPhoto};
+use crate::cocktails_api::schemas::measure::Units;
+use crate::localization::lang::Lang;
+use crate::utils::str_builder::StringBuilder;
+
+/// Drink compared with the ingredients the user has.
+#[derive(Debug, Clone)]
+pub struct IngredientMatch {
+    pub drink: Drink,
+    pub used: Vec<String>,
+    pub missing: Vec<String>,
+}
+
+impl IngredientMatch {
+    pub fn new(drink: Drink, have: &[String]) -> Self {
+        let (used, missing) = drink
+            .ingredients
+            .iter()
+            .map(|(ingredient, _)| ingredient.clone())
+            .partition(|ingredient| {
+                have.iter()
+                    .any(|have| have.trim().eq_ignore_ascii_case(ingredient.trim()))
+            });
+        Self {
+            drink,
+            used,
+            missing,
+        }
+    }
+
+    /// The more of the user's ingredients a drink uses and the less it misses, the higher it is.
+    pub fn rank(drinks: Vec<Drink>, have: &[String]) -> Vec<IngredientMatch> {
+        let mut matches = drinks
+            .into_iter()
+            .map(|drink| IngredientMatch::new(drink, have))
+            .collect::<Vec<IngredientMatch>>();
+        matches.sort_by(|first, second| {
+            second
+                .used
+                .len()
+                .cmp(&first.used.len())
+                .then(first.missing.len().cmp(&second.missing.len()))
+                .then(first.drink.name.cmp(&second.drink.name))
+        });
+        matches
+    }
+}
+
+pub struct LangMatch {
+    pub matched: IngredientMatch,
+    pub lang: Arc<Lang>,
+    pub units: Units,
+}
+
+impl LangMatch {
+    pub fn as_lang_drink(&self) -> LangDrink {
+        LangDrink {
+            drink: self.matched.drink.clone(),
+            lang: self.lang.clone(),
+            units: self.units,
+        }
+    }
+}
+
+impl WithPhoto for LangMatch {
+    fn get_url(&self) -> Option<String> {
+        self.matched.drink.image.clone()
+    }
+}
+
+impl Display for LangMatch {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        let names = |ingredients: &[String]| {
+            ingredients
+                .iter()
+                .map(|name| self.lang.names.ingredient(name))
+                .collect::<Vec<String>>()
+                .join(", ")
+        };
+        let result = StringBuilder::new()
+            .add(
+                &format!("{}: ", self.lang.service_responses.uses),
+                Some(names(&self.matched.used)),
+            )
+            .add(
+                &format!("{}: ", self.lang.service_responses.missing),
+                match self.matched.missing.is_empty() {
+                    true => None,
+                    false => Some(names(&self.matched.missing)),
+                },
+            )
+            .get_str();
+        write!(f, "{}\n{}", self.as_lang_drink(), result)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::cocktails_api::schemas::drink::Drink;
+    use crate::cocktails_api::schemas::matches::IngredientMatch;
+    use crate::utils::testing::DrinkBuilder;
+
+    fn drink(name: &str, ingredients: &[&str]) -> Drink {
+        DrinkBuilder::new(name).ingredients(ingredients).build()
+    }
+
+    #[test]
+    fn test_rank() {
+        let have = vec!["vodka".to_string(), "Lime juice".to_string()];
+        let result = IngredientMatch::rank(
+            vec![
+                drink("Vodka Martini", &["Vodka", "Dry Vermouth", "Olive"]),
+                drink("Moscow Mule", &["Vodka", "Lime juice", "Ginger ale"]),
+                drink("Vodka Tonic", &["Vodka", "Tonic water"]),
+            ],
+            &have,
+        );
+        let names = result
+            .iter()
+            .map(|matched| matched.drink.name.as_str())
+            .collect::<Vec<&str>>();
+        assert_eq!(names, vec!["Moscow Mule", "Vodka Tonic", "Vodka Martini"]);
+        assert_eq!(result[0].missing, vec!["Ginger ale"]);
+    }
+}
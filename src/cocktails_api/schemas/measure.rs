@@ -0,0 +1,328 @@
+use std::fmt::{Display, Formatter};
+
+use serde::{Deserialize, Serialize};
+
+/// How the user wants to see the measures of the ingredients.
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, Eq, Default)]
+pub enum Units {
+    /// As the cocktails source has them.
+    #[default]
+    Original,
+    Metric,
+    Imperial,
+}
+
+impl Units {
+    pub fn next(&self) -> Units {
+        match self {
+            Units::Original => Units::Metric,
+            Units::Metric => Units::Imperial,
+            Units::Imperial => Units::Original,
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Unit {
+    Ml,
+    Cl,
+    Oz,
+    Tsp,
+    Tbsp,
+    Cup,
+    Shot,
+    Dash,
+    Part,
+}
+
+impl Unit {
+    fn from_word(word: &str) -> Option<Unit> {
+        let word = word.trim_end_matches('.').to_lowercase();
+        match word.as_str() {
+            "ml" | "milliliter" | "milliliters" | "millilitre" | "millilitres" => Some(Unit::Ml),
+            "cl" | "centiliter" | "centiliters" | "centilitre" | "centilitres" => Some(Unit::Cl),
+            "oz" | "ounce" | "ounces" => Some(Unit::Oz),
+            "tsp" | "teaspoon" | "teaspoons" => Some(Unit::Tsp),
+            "tbsp" | "tblsp" | "tbl" | "tablespoon" | "tablespoons" => Some(Unit::Tbsp),
+            "cup" | "cups" => Some(Unit::Cup),
+            "shot" | "shots" | "jigger" | "jiggers" => Some(Unit::Shot),
+            "dash" | "dashes" => Some(Unit::Dash),
+            "part" | "parts" => Some(Unit::Part),
+            _ => None,
+        }
+    }
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Unit::Ml => "ml",
+            Unit::Cl => "cl",
+            Unit::Oz => "oz",
+            Unit::Tsp => "tsp",
+            Unit::Tbsp => "tbsp",
+            Unit::Cup => "cup",
+            Unit::Shot => "shot",
+            Unit::Dash => "dash",
+            Unit::Part => "part",
+        }
+    }
+
+    /// Millilitres in one unit, `None` for the units without a fixed volume.
+    pub fn ml(&self) -> Option<f64> {
+        match self {
+            Unit::Ml => Some(1.0),
+            Unit::Cl => Some(10.0),
+            Unit::Oz => Some(29.5735),
+            Unit::Tsp => Some(4.92892),
+            Unit::Tbsp => Some(14.7868),
+            Unit::Cup => Some(236.588),
+            Unit::Shot => Some(44.3603),
+            Unit::Dash | Unit::Part => None,
+        }
+    }
+}
+
+/// Parsed measure like `1 1/2 oz`, `2-3 dashes` or `1 cup crushed`.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Measure {
+    pub min: f64,
+    /// Same as `min` unless the measure is a range.
+    pub max: f64,
+    pub unit: Option<Unit>,
+    /// The unit as it was written, kept while the unit does not change.
+    pub word: String,
+    /// Free text after the amount, like `crushed` or `or to taste`.
+    pub rest: String,
+}
+
+impl Measure {
+    /// `None` for measures without an amount, like `Juice of 1/2` or `Top up`.
+    pub fn parse(text: &str) -> Option<Measure> {
+        let tokens = tokens(text);
+        let mut position = 0;
+        let min = amount(&tokens, &mut position)?;
+        let mut max = min;
+        if let Some(separator) = tokens.get(position) {
+            if ["-", "to", "or"].contains(&separator.to_lowercase().as_str()) {
+                let mut next = position + 1;
+                if let Some(second) = amount(&tokens, &mut next) {
+                    position = next;
+                    max = second;
+                }
+            }
+        }
+        let (min, max) = match max < min {
+            // `1-1/2 oz` is one and a half.
+            true if max < 1.0 => (min + max, min + max),
+            true => (max, min),
+            false => (min, max),
+        };
+        if tokens.get(position).map(String::as_str) == Some("fl") {
+            position += 1;
+        }
+        let (unit, word) = match tokens.get(position).and_then(|word| Unit::from_word(word)) {
+            Some(unit) => {
+                position += 1;
+                (Some(unit), tokens[position - 1].clone())
+            }
+            None => (None, String::new()),
+        };
+        Some(Measure {
+            min,
+            max,
+            unit,
+            word,
+            rest: tokens[position..].join(" "),
+        })
+    }
+
+    /// Converted measure text, the original text when there is nothing to convert.
+    pub fn convert_text(text: &str, units: Units) -> String {
+        match Measure::parse(text).and_then(|measure| measure.convert(units)) {
+            Some(measure) => measure.to_string(),
+            None => text.to_string(),
+        }
+    }
+
+    pub fn scale(&self, factor: f64) -> Measure {
+        Measure {
+            min: self.min * factor,
+            max: self.max * factor,
+            ..self.clone()
+        }
+    }
+
+    /// Volume in millilitres, the middle of a range.
+    pub fn ml(&self) -> Option<f64> {
+        Some((self.min + self.max) / 2.0 * self.unit?.ml()?)
+    }
+
+    /// `None` when the measure is already in the units or has no fixed volume.
+    pub fn convert(&self, units: Units) -> Option<Measure> {
+        let target = match (units, self.unit?) {
+            (Units::Metric, Unit::Oz | Unit::Cup | Unit::Shot) => Unit::Ml,
+            (Units::Imperial, Unit::Ml | Unit::Cl) => Unit::Oz,
+            _ => return None,
+        };
+        self.to_unit(target)
+    }
+
+    /// Same volume in another unit, `None` when either unit has no fixed volume.
+    pub fn to_unit(&self, target: Unit) -> Option<Measure> {
+        let factor = self.unit?.ml()? / target.ml()?;
+        Some(Measure {
+            min: self.min * factor,
+            max: self.max * factor,
+            unit: Some(target),
+            word: target.as_str().to_string(),
+            rest: self.rest.clone(),
+        })
+    }
+}
+
+impl Display for Measure {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        let amount = |value: f64| match self.unit {
+            Some(Unit::Ml) => format!("{}", value.round().max(1.0)),
+            Some(Unit::Cl) => format!("{}", (value * 10.0).round() / 10.0),
+            _ => fraction(value),
+        };
+        let mut text = amount(self.min);
+        if amount(self.max) != text {
+            text = format!("{}-{}", text, amount(self.max));
+        }
+        for part in [&self.word, &self.rest] {
+            if !part.is_empty() {
+                text = format!("{} {}", text, part);
+            }
+        }
+        write!(f, "{}", text)
+    }
+}
+
+/// Words of the measure, numbers are split from the glued units and `-` stands alone.
+fn tokens(text: &str) -> Vec<String> {
+    let mut spaced = String::with_capacity(text.len());
+    let mut previous = ' ';
+    for symbol in text.chars() {
+        match symbol {
+            '-' => spaced.push_str(" - "),
+            _ if previous.is_ascii_digit() && symbol.is_alphabetic() => {
+                spaced.push(' ');
+                spaced.push(symbol);
+            }
+            _ => spaced.push(symbol),
+        }
+        previous = symbol;
+    }
+    spaced.split_whitespace().map(str::to_string).collect()
+}
+
+/// Whole number, decimal, fraction or a whole number with a fraction.
+fn amount(tokens: &[String], position: &mut usize) -> Option<f64> {
+    let first = tokens.get(*position)?;
+    let mut value = number(first)?;
+    *position += 1;
+    if !first.contains('/') && value.fract() == 0.0 {
+        if let Some(next) = tokens.get(*position).filter(|next| next.contains('/')) {
+            if let Some(fraction) = number(next) {
+                value += fraction;
+                *position += 1;
+            }
+        }
+    }
+    Some(value)
+}
+
+fn number(token: &str) -> Option<f64> {
+    let value = match token.split_once('/') {
+        Some((numerator, denominator)) => {
+            numerator.parse::<f64>().ok()? / denominator.parse::<f64>().ok()?
+        }
+        None => token.replace(',', ".").parse::<f64>().ok()?,
+    };
+    match value.is_finite() && value >= 0.0 {
+        true => Some(value),
+        false => None,
+    }
+}
+
+/// Rounded to the nearest quarter or third, `1 1/2` rather than `1.5`.
+fn fraction(value: f64) -> String {
+    const PARTS: [(f64, &str); 5] = [
+        (0.25, "1/4"),
+        (1.0 / 3.0, "1/3"),
+        (0.5, "1/2"),
+        (2.0 / 3.0, "2/3"),
+        (0.75, "3/4"),
+    ];
+    let mut whole = value.trunc();
+    let rest = value - whole;
+    let (part, name) = PARTS
+        .iter()
+        .chain([(0.0, ""), (1.0, "")].iter())
+        .min_by(|first, second| {
+            (first.0 - rest)
+                .abs()
+                .partial_cmp(&(second.0 - rest).abs())
+                .unwrap_or(std::cmp::Ordering::Equal)
+        })
+        .copied()
+        .unwrap_or((0.0, ""));
+    if part == 1.0 {
+        whole += 1.0;
+    }
+    match (whole as u64, name) {
+        (0, "") => "0".to_string(),
+        (0, name) => name.to_string(),
+        (whole, "") => whole.to_string(),
+        (whole, name) => format!("{} {}", whole, name),
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::cocktails_api::schemas::measure::{Measure, Unit, Units};
+
+    #[test]
+    fn test_parse() {
+        let measure = Measure::parse("1 1/2 oz").unwrap();
+        assert_eq!((measure.min, measure.unit), (1.5, Some(Unit::Oz)));
+        let measure = Measure::parse("2-3 dashes").unwrap();
+        assert_eq!((measure.min, measure.max), (2.0, 3.0));
+        assert_eq!(measure.word, "dashes");
+        assert_eq!(Measure::parse("1-1/2 oz").unwrap().max, 1.5);
+        assert_eq!(Measure::parse("2cl").unwrap().unit, Some(Unit::Cl));
+        assert_eq!(Measure::parse("0,5 tsp").unwrap().min, 0.5);
+        assert_eq!(Measure::parse("1 cup crushed").unwrap().rest, "crushed");
+        assert_eq!(Measure::parse("2 parts").unwrap().unit, Some(Unit::Part));
+        assert_eq!(Measure::parse("3 slices").unwrap().unit, None);
+        assert_eq!(Measure::parse("Juice of 1/2"), None);
+        assert_eq!(Measure::parse("Top up"), None);
+    }
+
+    #[test]
+    fn test_convert() {
+        assert_eq!(Measure::convert_text("1 1/2 oz", Units::Metric), "44 ml");
+        assert_eq!(Measure::convert_text("1-2 oz", Units::Metric), "30-59 ml");
+        assert_eq!(Measure::convert_text("4 cl", Units::Imperial), "1 1/3 oz");
+        assert_eq!(
+            Measure::convert_text("1 1/2 oz", Units::Original),
+            "1 1/2 oz"
+        );
+        assert_eq!(Measure::convert_text("2 dashes", Units::Metric), "2 dashes");
+        assert_eq!(
+            Measure::convert_text("Juice of 1/2", Units::Metric),
+            "Juice of 1/2"
+        );
+    }
+
+    #[test]
+    fn test_scale() {
+        let measure = Measure::parse("1 1/2 oz").unwrap().scale(3.0);
+        assert_eq!(measure.to_string(), "4 1/2 oz");
+        let measure = Measure::parse("2-3 dashes").unwrap().scale(2.0);
+        assert_eq!(measure.to_string(), "4-6 dashes");
+        assert_eq!(Measure::parse("1-3 cl").unwrap().ml(), Some(20.0));
+        assert_eq!(Measure::parse("2 parts").unwrap().ml(), None);
+    }
+}
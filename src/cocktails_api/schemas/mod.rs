@@ -5,9 +5,15 @@ use serde::Deserialize;
 use crate::localization::lang::Lang;
 use crate::ErrorHandler;
 
+pub mod dataset;
 pub mod drink;
 pub mod ingredient;
 pub mod lists;
+pub mod matches;
+pub mod measure;
+pub mod scaling;
+pub mod shopping;
+pub mod taste;
 
 pub trait ToLangDrink<T> {
     type Output;
@@ -0,0 +1,191 @@
+use std::fmt::{Display, Formatter};
+
+use crate::cocktails_api::schemas::drink::LangDrink;
+use crate::cocktails_api::schemas::measure::Measure;
+
+pub const BOTTLE_ML: f64 = 700.0;
+/// Water a serving gets from the ice while it is stirred or shaken,
+/// a batch is diluted in advance.
+pub const DILUTION: f64 = 0.2;
+
+/// Recipe multiplied for a number of servings.
+pub struct LangServings {
+    pub drink: LangDrink,
+    pub servings: u32,
+}
+
+impl Display for LangServings {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        let lang = &self.drink.lang;
+        let factor = self.servings as f64;
+        let ingredients = self
+            .drink
+            .drink
+            .ingredients
+            .iter()
+            .map(|(name, measure)| {
+                let measure = match measure.as_deref().map(str::trim) {
+                    Some(text) => match Measure::parse(text) {
+                        Some(measure) => {
+                            let measure = measure.scale(factor);
+                            measure
+                                .convert(self.drink.units)
+                                .unwrap_or(measure)
+                                .to_string()
+                        }
+                        None => format!("{} ×{}", text, self.servings),
+                    },
+                    None => String::new(),
+                };
+                format!(" - {} {}", lang.names.ingredient(name), measure)
+            })
+            .collect::<Vec<String>>();
+        write!(
+            f,
+            "{}, {} {}\n{}",
+            self.drink.drink.name,
+            lang.scale.servings,
+            self.servings,
+            ingredients.join("\n")
+        )
+    }
+}
+
+/// Totals for a party, in litres and bottles, with the water the ice would add.
+pub struct LangBatch {
+    pub drink: LangDrink,
+    pub guests: u32,
+}
+
+impl LangBatch {
+    /// Millilitres of every ingredient, `None` for the ones without a volume.
+    pub fn totals(&self) -> Vec<(String, Option<f64>)> {
+        self.drink
+            .drink
+            .ingredients
+            .iter()
+            .map(|(name, measure)| {
+                let ml = measure
+                    .as_deref()
+                    .and_then(Measure::parse)
+                    .and_then(|measure| measure.ml());
+                (name.clone(), ml.map(|ml| ml * self.guests as f64))
+            })
+            .collect()
+    }
+
+    pub fn dilution(&self) -> f64 {
+        self.totals().iter().filter_map(|(_, ml)| *ml).sum::<f64>() * DILUTION
+    }
+
+    fn volume(ml: f64) -> String {
+        match ml < 1000.0 {
+            true => format!("{} ml", ml.round()),
+            false => format!("{} l", (ml / 100.0).round() / 10.0),
+        }
+    }
+}
+
+impl Display for LangBatch {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        let lang = &self.drink.lang;
+        let scale = &lang.scale;
+        let totals = self.totals();
+        let mut lines = totals
+            .iter()
+            .zip(self.drink.drink.ingredients.iter())
+            .map(|((name, ml), (_, measure))| {
+                let amount = match (ml, measure.as_deref().map(str::trim)) {
+                    (Some(ml), _) => format!(
+                        "{} ({:.1} {})",
+                        Self::volume(*ml),
+                        ml / BOTTLE_ML,
+                        scale.bottles
+                    ),
+                    (None, Some(text)) => match Measure::parse(text) {
+                        Some(measure) => measure.scale(self.guests as f64).to_string(),
+                        None => format!("{} ×{}", text, self.guests),
+                    },
+                    (None, None) => String::new(),
+                };
+                format!(" - {}: {}", lang.names.ingredient(name), amount)
+            })
+            .collect::<Vec<String>>();
+        let dilution = self.dilution();
+        let total = totals.iter().filter_map(|(_, ml)| *ml).sum::<f64>() + dilution;
+        lines.push(format!(" - {}: {}", scale.dilution, Self::volume(dilution)));
+        write!(
+            f,
+            "{} {} {}: {}\n{}\n{}: {} ({:.1} {})",
+            scale.batch_title,
+            self.guests,
+            scale.guests,
+            self.drink.drink.name,
+            lines.join("\n"),
+            scale.total,
+            Self::volume(total),
+            total / BOTTLE_ML,
+            scale.bottles
+        )
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::sync::Arc;
+
+    use crate::cocktails_api::schemas::drink::LangDrink;
+    use crate::cocktails_api::schemas::measure::Units;
+    use crate::cocktails_api::schemas::scaling::{LangBatch, LangServings};
+    use crate::localization::lang::Lang;
+    use crate::utils::testing::DrinkBuilder;
+
+    fn margarita(units: Units) -> LangDrink {
+        LangDrink {
+            drink: DrinkBuilder::new("Margarita")
+                .id("11007")
+                .measures(&[
+                    ("Tequila", Some("1 1/2 oz ")),
+                    ("Triple sec", Some("1 cl")),
+                    ("Salt", Some("Pinch")),
+                    ("Lime", None),
+                ])
+                .build(),
+            lang: Arc::new(Lang::default()),
+            units,
+        }
+    }
+
+    #[test]
+    fn test_servings() {
+        let text = LangServings {
+            drink: margarita(Units::Original),
+            servings: 4,
+        }
+        .to_string();
+        assert!(text.contains("Tequila 6 oz"));
+        assert!(text.contains("Triple sec 4 cl"));
+        assert!(text.contains("Salt Pinch ×4"));
+        let text = LangServings {
+            drink: margarita(Units::Metric),
+            servings: 2,
+        }
+        .to_string();
+        assert!(text.contains("Tequila 89 ml"));
+    }
+
+    #[test]
+    fn test_batch() {
+        let batch = LangBatch {
+            drink: margarita(Units::Original),
+            guests: 20,
+        };
+        let totals = batch.totals();
+        assert_eq!(totals[1], ("Triple sec".to_string(), Some(200.0)));
+        assert_eq!(totals[2].1, None);
+        assert!((batch.dilution() - (887.205 + 200.0) * 0.2).abs() < 0.01);
+        let text = batch.to_string();
+        assert!(text.contains("Tequila: 887 ml (1.3"));
+        assert!(text.contains("Triple sec: 200 ml"));
+    }
+}
@@ -0,0 +1,356 @@
+use std::collections::HashMap;
+use std::fmt::{Display, Formatter};
+use std::sync::Arc;
+
+use crate::cocktails_api::schemas::drink::Drink;
+use crate::cocktails_api::schemas::measure::{Measure, Unit, Units};
+use crate::localization::lang::Lang;
+
+const MIXERS: [&str; 10] = [
+    "juice",
+    "soft drink",
+    "soda",
+    "syrup",
+    "sweetener",
+    "water",
+    "mixer",
+    "cream",
+    "milk",
+    "coffee",
+];
+
+/// Shelf of the shop an ingredient comes from, the list is sorted in this order.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum Group {
+    Spirit,
+    Liqueur,
+    Mixer,
+    Other,
+}
+
+impl Group {
+    /// From `Ingredient::ty`, which is as detailed as `Vodka` or `Soft Drink`.
+    pub fn from_ty(ty: Option<&str>, alco: bool) -> Group {
+        let ty = ty.unwrap_or_default().to_lowercase();
+        if ty.contains("liqueur") || ty.contains("cordial") {
+            Group::Liqueur
+        } else if alco {
+            Group::Spirit
+        } else if MIXERS.iter().any(|mixer| ty.contains(mixer)) {
+            Group::Mixer
+        } else {
+            Group::Other
+        }
+    }
+
+    pub fn label(&self, lang: &Lang) -> String {
+        match self {
+            Group::Spirit => lang.shopping.spirit.clone(),
+            Group::Liqueur => lang.shopping.liqueur.clone(),
+            Group::Mixer => lang.shopping.mixer.clone(),
+            Group::Other => lang.shopping.other.clone(),
+        }
+    }
+}
+
+/// One ingredient with the measures of all the drinks summed up.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ShoppingItem {
+    pub name: String,
+    pub group: Group,
+    /// Total of the measures with a volume, in millilitres.
+    pub volume: Option<Measure>,
+    /// The unit all the volumes were written in, millilitres when they differ.
+    pub unit: Option<Unit>,
+    /// Totals without a volume, one per unit word, like `4 dashes` or `3 slices`.
+    pub counts: Vec<Measure>,
+    /// Measures which could not be parsed, like `Juice of 1`.
+    pub notes: Vec<String>,
+}
+
+impl ShoppingItem {
+    fn new(name: &str, group: Group) -> Self {
+        Self {
+            name: name.to_string(),
+            group,
+            volume: None,
+            unit: None,
+            counts: vec![],
+            notes: vec![],
+        }
+    }
+
+    fn add(&mut self, text: &str) {
+        let text = text.trim();
+        if text.is_empty() {
+            return;
+        }
+        let measure = match Measure::parse(text) {
+            Some(measure) => measure,
+            None => {
+                self.notes.push(text.to_string());
+                return;
+            }
+        };
+        match measure.to_unit(Unit::Ml) {
+            Some(ml) => {
+                self.unit = match self.volume {
+                    None => measure.unit,
+                    Some(_) if self.unit == measure.unit => self.unit,
+                    Some(_) => Some(Unit::Ml),
+                };
+                match &mut self.volume {
+                    Some(volume) => {
+                        volume.min += ml.min;
+                        volume.max += ml.max;
+                    }
+                    None => {
+                        self.volume = Some(Measure {
+                            rest: String::new(),
+                            ..ml
+                        })
+                    }
+                }
+            }
+            None => match self.counts.iter_mut().find(|count| {
+                count.word.to_lowercase() == measure.word.to_lowercase()
+                    && count.rest == measure.rest
+            }) {
+                Some(count) => {
+                    count.min += measure.min;
+                    count.max += measure.max;
+                }
+                None => self.counts.push(measure),
+            },
+        }
+    }
+
+    /// Everything to buy joined with `+`, empty when the drinks have no measures.
+    pub fn amount(&self, units: Units) -> String {
+        let mut parts = vec![];
+        if let Some(volume) = &self.volume {
+            let converted = match units {
+                Units::Original => self.unit.and_then(|unit| volume.to_unit(unit)),
+                _ => volume.convert(units),
+            };
+            parts.push(converted.unwrap_or_else(|| volume.clone()).to_string());
+        }
+        parts.extend(self.counts.iter().map(ToString::to_string));
+        parts.extend(self.notes.iter().cloned());
+        parts.join(" + ")
+    }
+}
+
+/// Ingredients of several drinks, merged and grouped by shelf.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ShoppingList {
+    pub drinks: Vec<String>,
+    /// Sorted by group, then by name.
+    pub items: Vec<ShoppingItem>,
+    /// Ingredients which are left out because the bar has them.
+    pub in_bar: Vec<String>,
+}
+
+impl ShoppingList {
+    /// `groups` by lowercase ingredient name, the missing ones are `Group::Other`.
+    pub fn new(drinks: &[Drink], bar: &[String], groups: &HashMap<String, Group>) -> Self {
+        let mut items: Vec<ShoppingItem> = vec![];
+        let mut in_bar: Vec<String> = vec![];
+        for (name, measure) in drinks.iter().flat_map(|drink| drink.ingredients.iter()) {
+            let name = name.trim();
+            let key = name.to_lowercase();
+            if bar.iter().any(|have| have.trim().to_lowercase() == key) {
+                if !in_bar.iter().any(|have| have.to_lowercase() == key) {
+                    in_bar.push(name.to_string());
+                }
+                continue;
+            }
+            let position = match items
+                .iter()
+                .position(|item| item.name.to_lowercase() == key)
+            {
+                Some(position) => position,
+                None => {
+                    let group = groups.get(&key).copied().unwrap_or(Group::Other);
+                    items.push(ShoppingItem::new(name, group));
+                    items.len() - 1
+                }
+            };
+            if let Some(measure) = measure {
+                items[position].add(measure);
+            }
+        }
+        items.sort_by(|first, second| {
+            first
+                .group
+                .cmp(&second.group)
+                .then_with(|| first.name.cmp(&second.name))
+        });
+        Self {
+            drinks: drinks.iter().map(|drink| drink.name.clone()).collect(),
+            items,
+            in_bar,
+        }
+    }
+}
+
+pub struct LangShopping {
+    pub list: ShoppingList,
+    pub lang: Arc<Lang>,
+    pub units: Units,
+}
+
+impl LangShopping {
+    /// `group,ingredient,amount` rows with the header of the language.
+    pub fn csv(&self) -> String {
+        let mut rows = vec![self.lang.shopping.csv_header.clone()];
+        rows.extend(self.list.items.iter().map(|item| {
+            [
+                item.group.label(&self.lang),
+                self.lang.names.ingredient(&item.name),
+                item.amount(self.units),
+            ]
+            .iter()
+            .map(|field| csv_field(field))
+            .collect::<Vec<String>>()
+            .join(",")
+        }));
+        rows.join("\n") + "\n"
+    }
+}
+
+impl Display for LangShopping {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        let shopping = &self.lang.shopping;
+        let mut lines = vec![format!(
+            "{} {}",
+            shopping.title,
+            self.list.drinks.join(", ")
+        )];
+        if self.list.items.is_empty() {
+            lines.push(shopping.nothing.clone());
+        }
+        let mut group = None;
+        for item in self.list.items.iter() {
+            if group != Some(item.group) {
+                group = Some(item.group);
+                lines.push(format!("\n{}:", item.group.label(&self.lang)));
+            }
+            let name = self.lang.names.ingredient(&item.name);
+            lines.push(match item.amount(self.units) {
+                amount if amount.is_empty() => format!(" - {}", name),
+                amount => format!(" - {}: {}", name, amount),
+            });
+        }
+        if !self.list.in_bar.is_empty() {
+            let names = self
+                .list
+                .in_bar
+                .iter()
+                .map(|name| self.lang.names.ingredient(name))
+                .collect::<Vec<String>>();
+            lines.push(format!("\n{}: {}", shopping.in_bar, names.join(", ")));
+        }
+        write!(f, "{}", lines.join("\n"))
+    }
+}
+
+fn csv_field(value: &str) -> String {
+    match value.contains([',', '"', '\n']) {
+        true => format!("\"{}\"", value.replace('"', "\"\"")),
+        false => value.to_string(),
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::collections::HashMap;
+    use std::sync::Arc;
+
+    use crate::cocktails_api::schemas::drink::Drink;
+    use crate::cocktails_api::schemas::measure::Units;
+    use crate::cocktails_api::schemas::shopping::{Group, LangShopping, ShoppingList};
+    use crate::localization::lang::Lang;
+    use crate::utils::testing::DrinkBuilder;
+
+    fn drink(name: &str, ingredients: &[(&str, Option<&str>)]) -> Drink {
+        DrinkBuilder::new(name).measures(ingredients).build()
+    }
+
+    fn list() -> ShoppingList {
+        let drinks = vec![
+            drink(
+                "Margarita",
+                &[
+                    ("Tequila", Some("1 1/2 oz ")),
+                    ("Lime juice", Some("1 oz")),
+                    ("Salt", None),
+                ],
+            ),
+            drink(
+                "Moscow Mule",
+                &[
+                    ("Vodka", Some("2 oz")),
+                    ("lime juice", Some("3 cl")),
+                    ("Bitters", Some("2 dashes")),
+                ],
+            ),
+            drink(
+                "Mojito",
+                &[
+                    ("Lime", Some("Juice of 1")),
+                    ("Bitters", Some("1-2 dashes")),
+                ],
+            ),
+        ];
+        let groups = HashMap::from([
+            ("tequila".to_string(), Group::Spirit),
+            ("lime juice".to_string(), Group::Mixer),
+        ]);
+        ShoppingList::new(&drinks, &["VODKA".to_string()], &groups)
+    }
+
+    #[test]
+    fn test_list() {
+        let list = list();
+        let names = list
+            .items
+            .iter()
+            .map(|item| item.name.as_str())
+            .collect::<Vec<&str>>();
+        assert_eq!(
+            names,
+            vec!["Tequila", "Lime juice", "Bitters", "Lime", "Salt"]
+        );
+        assert_eq!(list.in_bar, vec!["Vodka".to_string()]);
+        assert_eq!(list.items[0].amount(Units::Original), "1 1/2 oz");
+        assert_eq!(list.items[1].amount(Units::Original), "60 ml");
+        assert_eq!(list.items[1].amount(Units::Imperial), "2 oz");
+        assert_eq!(list.items[2].amount(Units::Metric), "3-4 dashes");
+        assert_eq!(list.items[3].amount(Units::Metric), "Juice of 1");
+        assert_eq!(list.items[4].amount(Units::Metric), "");
+    }
+
+    #[test]
+    fn test_groups() {
+        assert_eq!(Group::from_ty(Some("Liqueur"), true), Group::Liqueur);
+        assert_eq!(Group::from_ty(Some("Rum"), true), Group::Spirit);
+        assert_eq!(Group::from_ty(Some("Soft Drink"), false), Group::Mixer);
+        assert_eq!(Group::from_ty(None, false), Group::Other);
+    }
+
+    #[test]
+    fn test_csv() {
+        let shopping = LangShopping {
+            list: list(),
+            lang: Arc::new(Lang::default()),
+            units: Units::Original,
+        };
+        let csv = shopping.csv();
+        assert!(csv.contains("\nSpirits,Tequila,1 1/2 oz\n"));
+        assert!(csv.contains("\nOther,Lime,Juice of 1\n"));
+        let text = shopping.to_string();
+        assert!(text.contains(" - Lime juice: 60 ml"));
+        assert!(text.contains("Already in your bar: Vodka"));
+    }
+}
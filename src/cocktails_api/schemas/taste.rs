@@ -0,0 +1,184 @@
+use std::collections::HashMap;
+use std::fmt::{Display, Formatter};
+use std::sync::Arc;
+
+use crate::cocktails_api::schemas::drink::{Drink, LangDrink, WithPhoto};
+use crate::cocktails_api::schemas::measure::Units;
+use crate::localization::lang::Lang;
+
+/// A shared category counts as much as this many shared ingredients.
+const CATEGORY_WEIGHT: i64 = 2;
+
+/// What the user likes, built from the drinks in their history.
+#[derive(Debug, Default)]
+pub struct Taste {
+    ingredients: HashMap<String, i64>,
+    categories: HashMap<String, i64>,
+}
+
+impl Taste {
+    /// Disliked drinks come with a negative weight.
+    pub fn new(drinks: &[(Drink, i64)]) -> Self {
+        let mut taste = Self::default();
+        for (drink, weight) in drinks {
+            for (ingredient, _) in drink.ingredients.iter() {
+                *taste
+                    .ingredients
+                    .entry(ingredient.trim().to_lowercase())
+                    .or_default() += weight;
+            }
+            if let Some(category) = &drink.category {
+                *taste.categories.entry(category.clone()).or_default() += weight;
+            }
+        }
+        taste
+    }
+
+    /// Nothing is liked yet.
+    pub fn is_empty(&self) -> bool {
+        !self.ingredients.values().any(|weight| *weight > 0)
+    }
+
+    pub fn score(&self, drink: &Drink) -> i64 {
+        let ingredients = drink
+            .ingredients
+            .iter()
+            .filter_map(|(ingredient, _)| self.ingredients.get(&ingredient.trim().to_lowercase()))
+            .sum::<i64>();
+        let category = drink
+            .category
+            .as_ref()
+            .and_then(|category| self.categories.get(category))
+            .copied()
+            .unwrap_or_default();
+        ingredients + category * CATEGORY_WEIGHT
+    }
+
+    /// Liked ingredients, the most liked first.
+    pub fn favorite_ingredients(&self, count: usize) -> Vec<String> {
+        Self::favorites(&self.ingredients)
+            .into_iter()
+            .take(count)
+            .collect()
+    }
+
+    pub fn favorite_category(&self) -> Option<String> {
+        Self::favorites(&self.categories).into_iter().next()
+    }
+
+    /// Drinks which fit the taste, the best first.
+    pub fn rank(&self, drinks: Vec<Drink>) -> Vec<Recommendation> {
+        let mut result = drinks
+            .into_iter()
+            .map(|drink| Recommendation {
+                score: self.score(&drink),
+                drink,
+            })
+            .filter(|recommendation| recommendation.score > 0)
+            .collect::<Vec<Recommendation>>();
+        result.sort_by(|first, second| {
+            second
+                .score
+                .cmp(&first.score)
+                .then(first.drink.name.cmp(&second.drink.name))
+        });
+        result
+    }
+
+    fn favorites(weights: &HashMap<String, i64>) -> Vec<String> {
+        let mut favorites = weights
+            .iter()
+            .filter(|(_, weight)| **weight > 0)
+            .collect::<Vec<(&String, &i64)>>();
+        favorites.sort_by(|first, second| second.1.cmp(first.1).then(first.0.cmp(second.0)));
+        favorites
+            .into_iter()
+            .map(|(name, _)| name.clone())
+            .collect()
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct Recommendation {
+    pub drink: Drink,
+    pub score: i64,
+}
+
+pub struct LangRecommendation {
+    pub recommendation: Recommendation,
+    pub lang: Arc<Lang>,
+    pub units: Units,
+}
+
+impl LangRecommendation {
+    pub fn as_lang_drink(&self) -> LangDrink {
+        LangDrink {
+            drink: self.recommendation.drink.clone(),
+            lang: self.lang.clone(),
+            units: self.units,
+        }
+    }
+}
+
+impl WithPhoto for LangRecommendation {
+    fn get_url(&self) -> Option<String> {
+        self.recommendation.drink.image.clone()
+    }
+}
+
+impl Display for LangRecommendation {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.as_lang_drink())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::cocktails_api::schemas::drink::Drink;
+    use crate::cocktails_api::schemas::taste::Taste;
+    use crate::utils::testing::DrinkBuilder;
+
+    fn drink(name: &str, category: &str, ingredients: &[&str]) -> Drink {
+        DrinkBuilder::new(name)
+            .category(category)
+            .ingredients(ingredients)
+            .build()
+    }
+
+    #[test]
+    fn test_rank() {
+        let taste = Taste::new(&[
+            (
+                drink("Mojito", "Cocktail", &["Light rum", "Lime", "Mint"]),
+                3,
+            ),
+            (
+                drink("Screwdriver", "Ordinary Drink", &["Vodka", "Orange juice"]),
+                -4,
+            ),
+        ]);
+        assert!(!taste.is_empty());
+        assert_eq!(taste.favorite_ingredients(1), vec!["light rum"]);
+        assert_eq!(taste.favorite_category().unwrap(), "Cocktail");
+
+        let result = taste.rank(vec![
+            drink("Vodka Mint", "Ordinary Drink", &["Vodka", "Mint"]),
+            drink("Daiquiri", "Shot", &["Light rum", "lime"]),
+            drink("Mint Julep", "Cocktail", &["Bourbon", "Mint"]),
+        ]);
+        let names = result
+            .iter()
+            .map(|recommendation| recommendation.drink.name.as_str())
+            .collect::<Vec<&str>>();
+        assert_eq!(names, vec!["Mint Julep", "Daiquiri"]);
+    }
+
+    #[test]
+    fn test_empty() {
+        let taste = Taste::new(&[(drink("Screwdriver", "Cocktail", &["Vodka"]), -4)]);
+        assert!(taste.is_empty());
+        assert!(taste
+            .rank(vec![drink("Vodka Tonic", "Cocktail", &["Vodka"])])
+            .is_empty());
+    }
+}
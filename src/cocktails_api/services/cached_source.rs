@@ -0,0 +1,457 @@
+use std::collections::HashMap;
+use std::fmt::{Display, Formatter};
+use std::future::Future;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+
+use async_trait::async_trait;
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+use serde_json::Value;
+use sqlx::SqlitePool;
+
+use crate::cocktails_api::schemas::drink::{Drink, LazyDrink};
+use crate::cocktails_api::schemas::ingredient::Ingredient;
+use crate::cocktails_api::schemas::lists::List;
+use crate::cocktails_api::services::source::CocktailSource;
+use crate::error::error_handler::{ErrorHandler, ErrorType};
+use crate::storage;
+
+const MINUTE: i64 = 60;
+const HOUR: i64 = 60 * MINUTE;
+/// Misses between the stats lines in the log.
+const STATS_EVERY: u64 = 100;
+
+#[derive(Debug, Clone, Copy)]
+pub enum Endpoint {
+    DrinkByName,
+    IngredientByName,
+    FirstLetter,
+    LookupById,
+    AllIngredients,
+    AllCategories,
+    FilterByIngredient,
+    FilterByCategory,
+}
+
+impl Endpoint {
+    /// Lists almost never change, searches are refreshed more often.
+    pub fn ttl(&self) -> i64 {
+        match self {
+            Endpoint::AllIngredients | Endpoint::AllCategories | Endpoint::LookupById => 24 * HOUR,
+            Endpoint::FirstLetter => 6 * HOUR,
+            Endpoint::FilterByIngredient | Endpoint::FilterByCategory => 6 * HOUR,
+            Endpoint::DrinkByName | Endpoint::IngredientByName => HOUR,
+        }
+    }
+
+    fn key(&self, query: &str) -> String {
+        format!("{:?}:{}", self, query.trim().to_lowercase())
+    }
+}
+
+struct CacheEntry {
+    value: Value,
+    expires_at: i64,
+    inserted_at: i64,
+}
+
+#[derive(Debug, Default, Clone, Copy)]
+pub struct CacheStats {
+    pub hits: u64,
+    pub misses: u64,
+    pub size: usize,
+}
+
+impl Display for CacheStats {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "cache: {} hits, {} misses, {} entries",
+            self.hits, self.misses, self.size
+        )
+    }
+}
+
+/// Caches responses of the inner source, keyed by endpoint + query.
+pub struct CachedSource {
+    inner: Arc<dyn CocktailSource>,
+    entries: Mutex<HashMap<String, CacheEntry>>,
+    capacity: usize,
+    pool: Option<SqlitePool>,
+    hits: AtomicU64,
+    misses: AtomicU64,
+}
+
+impl CachedSource {
+    pub fn new(inner: Arc<dyn CocktailSource>, capacity: usize) -> Self {
+        Self {
+            inner,
+            entries: Mutex::new(HashMap::new()),
+            capacity,
+            pool: None,
+            hits: AtomicU64::new(0),
+            misses: AtomicU64::new(0),
+        }
+    }
+
+    /// Persists entries in the `response_cache` table, so they survive restarts.
+    /// The table keeps at most `capacity` entries too.
+    pub async fn with_persistence(mut self, pool: SqlitePool) -> Result<Self, ErrorHandler> {
+        sqlx::query(
+            "CREATE TABLE IF NOT EXISTS response_cache (
+                key TEXT PRIMARY KEY,
+                value TEXT NOT NULL,
+                expires_at INTEGER NOT NULL,
+                inserted_at INTEGER NOT NULL DEFAULT 0
+            )",
+        )
+        .execute(&pool)
+        .await?;
+        let (has_inserted_at,) = sqlx::query_as::<_, (i64,)>(
+            "SELECT COUNT(*) FROM pragma_table_info('response_cache') WHERE name = 'inserted_at'",
+        )
+        .fetch_one(&pool)
+        .await?;
+        if has_inserted_at == 0 {
+            sqlx::query(
+                "ALTER TABLE response_cache ADD COLUMN inserted_at INTEGER NOT NULL DEFAULT 0",
+            )
+            .execute(&pool)
+            .await?;
+        }
+        self.pool = Some(pool);
+        self.evict_persisted(storage::now()).await?;
+        Ok(self)
+    }
+
+    pub fn stats(&self) -> CacheStats {
+        CacheStats {
+            hits: self.hits.load(Ordering::Relaxed),
+            misses: self.misses.load(Ordering::Relaxed),
+            size: self
+                .entries
+                .lock()
+                .map(|entries| entries.len())
+                .unwrap_or(0),
+        }
+    }
+
+    async fn cached<T, F, Fut>(
+        &self,
+        endpoint: Endpoint,
+        query: &str,
+        fetch: F,
+    ) -> Result<Option<Vec<T>>, ErrorHandler>
+    where
+        T: Serialize + DeserializeOwned,
+        F: FnOnce() -> Fut,
+        Fut: Future<Output = Result<Option<Vec<T>>, ErrorHandler>>,
+    {
+        let key = endpoint.key(query);
+        let now = storage::now();
+
+        if let Some(value) = self.get_memory(&key, now)? {
+            match serde_json::from_value(value) {
+                Ok(result) => {
+                    self.hits.fetch_add(1, Ordering::Relaxed);
+                    return Ok(result);
+                }
+                Err(error) => {
+                    log::warn!("Drop the cached {}: {}", key, error);
+                    self.lock()?.remove(&key);
+                }
+            }
+        }
+        if let Some((text, expires_at)) = self.get_persisted(&key, now).await? {
+            // Rows written before a schema change do not parse any more, they are refetched.
+            match serde_json::from_str::<Option<Vec<T>>>(&text) {
+                Ok(result) => {
+                    self.hits.fetch_add(1, Ordering::Relaxed);
+                    self.put_memory(key, serde_json::to_value(&result)?, expires_at, now)?;
+                    return Ok(result);
+                }
+                Err(error) => {
+                    log::warn!("Drop the persisted {}: {}", key, error);
+                    self.delete_persisted(&key).await?;
+                }
+            }
+        }
+
+        let misses = self.misses.fetch_add(1, Ordering::Relaxed) + 1;
+        let result = fetch().await?;
+        let value = serde_json::to_value(&result)?;
+        let expires_at = now + endpoint.ttl();
+        self.put_persisted(&key, &value, expires_at, now).await?;
+        self.put_memory(key, value, expires_at, now)?;
+        if misses.is_multiple_of(STATS_EVERY) {
+            log::info!("{}", self.stats());
+        }
+        Ok(result)
+    }
+
+    fn get_memory(&self, key: &str, now: i64) -> Result<Option<Value>, ErrorHandler> {
+        let mut entries = self.lock()?;
+        match entries.get(key) {
+            Some(entry) if entry.expires_at > now => Ok(Some(entry.value.clone())),
+            Some(_) => {
+                entries.remove(key);
+                Ok(None)
+            }
+            None => Ok(None),
+        }
+    }
+
+    fn put_memory(
+        &self,
+        key: String,
+        value: Value,
+        expires_at: i64,
+        now: i64,
+    ) -> Result<(), ErrorHandler> {
+        let mut entries = self.lock()?;
+        if entries.len() >= self.capacity && !entries.contains_key(&key) {
+            entries.retain(|_, entry| entry.expires_at > now);
+        }
+        if entries.len() >= self.capacity && !entries.contains_key(&key) {
+            let oldest = entries
+                .iter()
+                .min_by_key(|(_, entry)| entry.inserted_at)
+                .map(|(key, _)| key.clone());
+            if let Some(oldest) = oldest {
+                entries.remove(&oldest);
+            }
+        }
+        entries.insert(
+            key,
+            CacheEntry {
+                value,
+                expires_at,
+                inserted_at: now,
+            },
+        );
+        Ok(())
+    }
+
+    async fn get_persisted(
+        &self,
+        key: &str,
+        now: i64,
+    ) -> Result<Option<(String, i64)>, ErrorHandler> {
+        match &self.pool {
+            Some(pool) => Ok(sqlx::query_as::<_, (String, i64)>(
+                "SELECT value, expires_at FROM response_cache WHERE key = ? AND expires_at > ?",
+            )
+            .bind(key)
+            .bind(now)
+            .fetch_optional(pool)
+            .await?),
+            None => Ok(None),
+        }
+    }
+
+    async fn put_persisted(
+        &self,
+        key: &str,
+        value: &Value,
+        expires_at: i64,
+        now: i64,
+    ) -> Result<(), ErrorHandler> {
+        if let Some(pool) = &self.pool {
+            sqlx::query(
+                "INSERT OR REPLACE INTO response_cache (key, value, expires_at, inserted_at)
+                VALUES (?, ?, ?, ?)",
+            )
+            .bind(key)
+            .bind(value.to_string())
+            .bind(expires_at)
+            .bind(now)
+            .execute(pool)
+            .await?;
+            self.evict_persisted(now).await?;
+        }
+        Ok(())
+    }
+
+    async fn delete_persisted(&self, key: &str) -> Result<(), ErrorHandler> {
+        if let Some(pool) = &self.pool {
+            sqlx::query("DELETE FROM response_cache WHERE key = ?")
+                .bind(key)
+                .execute(pool)
+                .await?;
+        }
+        Ok(())
+    }
+
+    /// Expired rows go first, then the oldest ones above the capacity.
+    async fn evict_persisted(&self, now: i64) -> Result<(), ErrorHandler> {
+        if let Some(pool) = &self.pool {
+            sqlx::query("DELETE FROM response_cache WHERE expires_at <= ?")
+                .bind(now)
+                .execute(pool)
+                .await?;
+            sqlx::query(
+                "DELETE FROM response_cache WHERE key NOT IN (
+                    SELECT key FROM response_cache ORDER BY inserted_at DESC LIMIT ?
+                )",
+            )
+            .bind(self.capacity as i64)
+            .execute(pool)
+            .await?;
+        }
+        Ok(())
+    }
+
+    /// Rows in the `response_cache` table.
+    pub async fn persisted_size(&self) -> Result<i64, ErrorHandler> {
+        match &self.pool {
+            Some(pool) => Ok(
+                sqlx::query_as::<_, (i64,)>("SELECT COUNT(*) FROM response_cache")
+                    .fetch_one(pool)
+                    .await?
+                    .0,
+            ),
+            None => Ok(0),
+        }
+    }
+
+    fn lock(&self) -> Result<std::sync::MutexGuard<'_, HashMap<String, CacheEntry>>, ErrorHandler> {
+        self.entries.lock().map_err(|_| ErrorHandler {
+            msg: "Cache lock is poisoned.".to_string(),
+            ty: ErrorType::Unexpected,
+        })
+    }
+}
+
+#[async_trait]
+impl CocktailSource for CachedSource {
+    async fn drinks_by_name(&self, name: &str) -> Result<Option<Vec<Drink>>, ErrorHandler> {
+        self.cached(Endpoint::DrinkByName, name, || {
+            self.inner.drinks_by_name(name)
+        })
+        .await
+    }
+
+    async fn ingredients_by_name(
+        &self,
+        name: &str,
+    ) -> Result<Option<Vec<Ingredient>>, ErrorHandler> {
+        self.cached(Endpoint::IngredientByName, name, || {
+            self.inner.ingredients_by_name(name)
+        })
+        .await
+    }
+
+    async fn drinks_by_first_letter(
+        &self,
+        letter: &char,
+    ) -> Result<Option<Vec<Drink>>, ErrorHandler> {
+        self.cached(Endpoint::FirstLetter, &letter.to_string(), || {
+            self.inner.drinks_by_first_letter(letter)
+        })
+        .await
+    }
+
+    async fn lookup_by_id(&self, id: &str) -> Result<Option<Drink>, ErrorHandler> {
+        let result = self
+            .cached(Endpoint::LookupById, id, || async {
+                Ok(self.inner.lookup_by_id(id).await?.map(|drink| vec![drink]))
+            })
+            .await?;
+        Ok(result.and_then(|mut drinks| drinks.pop()))
+    }
+
+    /// Never cached, every call should give another drink.
+    async fn random_drink(&self) -> Result<Option<Drink>, ErrorHandler> {
+        self.inner.random_drink().await
+    }
+
+    async fn all_ingredients(&self) -> Result<Option<Vec<List>>, ErrorHandler> {
+        self.cached(Endpoint::AllIngredients, "", || {
+            self.inner.all_ingredients()
+        })
+        .await
+    }
+
+    async fn all_categories(&self) -> Result<Option<Vec<List>>, ErrorHandler> {
+        self.cached(Endpoint::AllCategories, "", || self.inner.all_categories())
+            .await
+    }
+
+    async fn filter_by_ingredient(
+        &self,
+        name: &str,
+    ) -> Result<Option<Vec<LazyDrink>>, ErrorHandler> {
+        self.cached(Endpoint::FilterByIngredient, name, || {
+            self.inner.filter_by_ingredient(name)
+        })
+        .await
+    }
+
+    async fn filter_by_category(&self, name: &str) -> Result<Option<Vec<LazyDrink>>, ErrorHandler> {
+        self.cached(Endpoint::FilterByCategory, name, || {
+            self.inner.filter_by_category(name)
+        })
+        .await
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::sync::Arc;
+
+    use crate::cocktails_api::services::cached_source::CachedSource;
+    use crate::cocktails_api::services::coctail_service::tests::FIXTURE;
+    use crate::cocktails_api::services::local_source::LocalSource;
+    use crate::cocktails_api::services::source::CocktailSource;
+    use crate::utils::testing::test_pool;
+
+    fn source(capacity: usize) -> CachedSource {
+        CachedSource::new(
+            Arc::new(LocalSource::from_file(FIXTURE).expect("Fail to load fixture.")),
+            capacity,
+        )
+    }
+
+    #[tokio::test]
+    async fn test_hits_and_misses() {
+        let cache = source(8);
+        cache.all_categories().await.unwrap();
+        cache.all_categories().await.unwrap();
+        cache.drinks_by_name("Mojito").await.unwrap();
+        let stats = cache.stats();
+        assert_eq!(stats.hits, 1);
+        assert_eq!(stats.misses, 2);
+    }
+
+    #[tokio::test]
+    async fn test_size_bound() {
+        let cache = source(2);
+        for name in ["Mojito", "Margarita", "Vodka"] {
+            cache.drinks_by_name(name).await.unwrap();
+        }
+        assert_eq!(cache.stats().size, 2);
+    }
+
+    #[tokio::test]
+    async fn test_persisted() {
+        let pool = test_pool().await;
+        let cache = source(2).with_persistence(pool.clone()).await.unwrap();
+        // Written before `Drink` got the fields it has now.
+        sqlx::query("INSERT INTO response_cache (key, value, expires_at) VALUES (?, ?, ?)")
+            .bind("LookupById:11007")
+            .bind("[{\"name\": \"Margarita\"}]")
+            .bind(i64::MAX)
+            .execute(&pool)
+            .await
+            .unwrap();
+        let drink = cache.lookup_by_id("11007").await.unwrap().unwrap();
+        assert_eq!(drink.name, "Margarita");
+        assert_eq!(cache.stats().misses, 1);
+
+        for name in ["Mojito", "Margarita", "Vodka"] {
+            cache.drinks_by_name(name).await.unwrap();
+        }
+        assert_eq!(cache.persisted_size().await.unwrap(), 2);
+    }
+}
@@ -1,201 +1,526 @@
+use std::collections::HashMap;
 use std::sync::Arc;
 
-use serde::de::DeserializeOwned;
-use serde_json::Value;
-
-use crate::cocktails_api::schemas::drink::{LangDrink, LangLazyDrink, LazyDrink};
-use crate::cocktails_api::schemas::ingredient::{Ingredient, LangIngredient};
-use crate::cocktails_api::schemas::lists::{LangList, List};
-use crate::cocktails_api::schemas::{RawDrinkListSchema, ToLangDrink};
+use crate::cocktails_api::schemas::drink::{LangDrink, LangLazyDrink};
+use crate::cocktails_api::schemas::ingredient::LangIngredient;
+use crate::cocktails_api::schemas::lists::LangList;
+use crate::cocktails_api::schemas::matches::{IngredientMatch, LangMatch};
+use crate::cocktails_api::schemas::measure::Units;
+use crate::cocktails_api::schemas::shopping::{Group, LangShopping, ShoppingList};
+use crate::cocktails_api::schemas::taste::{LangRecommendation, Taste};
+use crate::cocktails_api::schemas::ToLangDrink;
+use crate::cocktails_api::services::source::CocktailSource;
 use crate::error::error_handler::ErrorHandler;
 use crate::localization::lang::Lang;
 use crate::ErrorType;
 
-pub struct DrinksService;
+const BAR_CANDIDATES: usize = 40;
+const TASTE_INGREDIENTS: usize = 3;
+const RECOMMEND_CANDIDATES: usize = 30;
 
-const DRINK_BY_NAME_URL: &str = "https://thecocktaildb.com/api/json/v1/1/search.php?s=";
-const INGREDIENT_BY_NAME_URL: &str = "https://thecocktaildb.com/api/json/v1/1/search.php?i=";
-const ALL_INGREDIENTS_URL: &str = "https://thecocktaildb.com/api/json/v1/1/list.php?i=list";
-const ALL_CATEGORY: &str = "https://thecocktaildb.com/api/json/v1/1/list.php?c=list";
-const SEARCH_BY_INGREDIENT: &str = "https://www.thecocktaildb.com/api/json/v1/1/filter.php?i=";
-const SEARCH_BY_CATEGORY: &str = "https://www.thecocktaildb.com/api/json/v1/1/filter.php?c=";
-const SEARCH_BY_FIRST_LATTER: &str = "https://www.thecocktaildb.com/api/json/v1/1/search.php?f=";
+/// Wraps a `CocktailSource` and localizes everything it returns.
+pub struct DrinksService {
+    source: Arc<dyn CocktailSource>,
+}
 
 impl DrinksService {
+    pub fn new(source: Arc<dyn CocktailSource>) -> Self {
+        Self { source }
+    }
+
     pub async fn get_drink_by_name(
+        &self,
         name: &str,
         lang: Lang,
+        units: Units,
     ) -> Result<Option<Vec<LangDrink>>, ErrorHandler> {
-        if let Some(drinks) = Self::send_request::<Value>(DRINK_BY_NAME_URL, Some(name)).await? {
-            let mut vec_drinks = Vec::with_capacity(drinks.len());
-            let lang = Arc::new(lang);
-            for drink in drinks {
-                vec_drinks.push(LangDrink::new(drink, lang.clone())?);
-            }
-
-            Ok(Some(vec_drinks))
-        } else {
-            Ok(None)
-        }
+        let result = self
+            .source
+            .drinks_by_name(name)
+            .await?
+            .map(|drinks| Self::in_units(Self::to_lazy(drinks, Arc::new(lang)), units));
+        Ok(result)
     }
 
     pub async fn get_ingredient_by_name(
+        &self,
         name: &str,
         lang: Lang,
     ) -> Result<Option<Vec<LangIngredient>>, ErrorHandler> {
-        let lang = Arc::new(lang);
-        let result = Self::send_request::<Ingredient>(INGREDIENT_BY_NAME_URL, Some(name))
+        let result = self
+            .source
+            .ingredients_by_name(&lang.names.ingredient_key(name))
             .await?
-            .map(|ingredients| {
-                ingredients
-                    .into_iter()
-                    .filter_map(|ing| match LangIngredient::new(ing, lang.clone()) {
-                        Err(_) => None,
-                        Ok(ing) => Some(ing),
-                    })
-                    .collect::<Vec<LangIngredient>>()
-            });
+            .map(|ingredients| Self::to_lazy(ingredients, Arc::new(lang)));
         Ok(result)
     }
 
     pub async fn search_by_first_letter(
+        &self,
         letter: &char,
         lang: Lang,
+        units: Units,
     ) -> Result<Option<Vec<LangDrink>>, ErrorHandler> {
-        if let Some(result) =
-            Self::send_request::<Value>(SEARCH_BY_FIRST_LATTER, Some(&format!("{}", letter)))
-                .await?
-        {
-            let lang = Arc::new(lang);
-            return Ok(Some(
-                result
-                    .into_iter()
-                    .filter_map(|value| match LangDrink::new(value, lang.clone()) {
-                        Ok(result) => Some(result),
-                        Err(_) => None,
-                    })
-                    .collect::<Vec<LangDrink>>(),
-            ));
-        }
-        Ok(None)
+        let result = self
+            .source
+            .drinks_by_first_letter(letter)
+            .await?
+            .map(|drinks| Self::in_units(Self::to_lazy(drinks, Arc::new(lang)), units));
+        Ok(result)
     }
 
-    pub async fn get_all_ingredients(lang: Lang) -> Result<Vec<LangList>, ErrorHandler> {
-        let result = Self::send_request::<List>(ALL_INGREDIENTS_URL, None)
-            .await?
-            .ok_or(ErrorHandler {
-                msg: "Exception in the cocktail Service.".to_string(),
-                ty: ErrorType::Service,
-            })?;
+    pub async fn random_drink(
+        &self,
+        lang: Lang,
+        units: Units,
+    ) -> Result<Option<LangDrink>, ErrorHandler> {
+        let result = self.source.random_drink().await?.map(|drink| LangDrink {
+            drink,
+            lang: Arc::new(lang),
+            units,
+        });
+        Ok(result)
+    }
+
+    pub async fn lookup_by_id(
+        &self,
+        id: &str,
+        lang: Lang,
+        units: Units,
+    ) -> Result<Option<LangDrink>, ErrorHandler> {
+        let result = self.source.lookup_by_id(id).await?.map(|drink| LangDrink {
+            drink,
+            lang: Arc::new(lang),
+            units,
+        });
+        Ok(result)
+    }
+
+    pub async fn get_all_ingredients(&self, lang: Lang) -> Result<Vec<LangList>, ErrorHandler> {
+        let result = self.source.all_ingredients().await?.ok_or(ErrorHandler {
+            msg: "Exception in the cocktail Service.".to_string(),
+            ty: ErrorType::Service,
+        })?;
         let result = Self::to_lazy(result, Arc::new(lang));
 
         Ok(result)
     }
 
     pub async fn find_by_ingredient(
+        &self,
         name: &str,
         lang: Lang,
     ) -> Result<Option<Vec<LangLazyDrink>>, ErrorHandler> {
-        let result = Self::send_request::<LazyDrink>(SEARCH_BY_INGREDIENT, Some(name))
+        let result = self
+            .source
+            .filter_by_ingredient(&lang.names.ingredient_key(name))
             .await?
             .map(|drinks| Self::to_lazy(drinks, Arc::new(lang)));
         Ok(result)
     }
 
-    pub async fn get_all_category(lang: Lang) -> Result<Vec<LangList>, ErrorHandler> {
-        let result = Self::send_request::<List>(ALL_CATEGORY, None)
+    /// Drinks which use the most of the given ingredients, at most `limit` of them.
+    pub async fn find_by_ingredients(
+        &self,
+        names: &[String],
+        limit: usize,
+        lang: Lang,
+        units: Units,
+    ) -> Result<Vec<LangMatch>, ErrorHandler> {
+        let names = names
+            .iter()
+            .map(|name| lang.names.ingredient_key(name))
+            .collect::<Vec<String>>();
+        let mut drinks = Vec::with_capacity(limit);
+        for id in self.candidates(&names).await?.into_iter().take(limit) {
+            if let Some(drink) = self.source.lookup_by_id(&id).await? {
+                drinks.push(drink);
+            }
+        }
+        let lang = Arc::new(lang);
+        Ok(IngredientMatch::rank(drinks, &names)
+            .into_iter()
+            .map(|matched| LangMatch {
+                matched,
+                lang: lang.clone(),
+                units,
+            })
+            .collect())
+    }
+
+    /// Ids of the drinks with any of the ingredients, the most used ones first.
+    async fn candidates(&self, names: &[String]) -> Result<Vec<String>, ErrorHandler> {
+        let mut counter: HashMap<String, usize> = HashMap::new();
+        for name in names.iter() {
+            if let Some(drinks) = self.source.filter_by_ingredient(name).await? {
+                for drink in drinks {
+                    *counter.entry(drink.id).or_default() += 1;
+                }
+            }
+        }
+        let mut ids = counter.into_iter().collect::<Vec<(String, usize)>>();
+        ids.sort_by(|first, second| second.1.cmp(&first.1).then(first.0.cmp(&second.0)));
+        Ok(ids.into_iter().map(|(id, _)| id).collect())
+    }
+
+    /// Drinks which can be made from the bar, or miss only one ingredient.
+    /// Only the candidates found under the most of the bar are looked up.
+    pub async fn what_can_i_make(
+        &self,
+        bar: &[String],
+        lang: Lang,
+        units: Units,
+    ) -> Result<Vec<LangMatch>, ErrorHandler> {
+        let bar = bar
+            .iter()
+            .map(|name| lang.names.ingredient_key(name))
+            .collect::<Vec<String>>();
+        let mut result = vec![];
+        for id in self
+            .candidates(&bar)
             .await?
-            .ok_or(ErrorHandler {
-                msg: "Fail to get All.".to_string(),
-                ty: ErrorType::Unexpected,
-            })?;
+            .into_iter()
+            .take(BAR_CANDIDATES)
+        {
+            if let Some(drink) = self.source.lookup_by_id(&id).await? {
+                let matched = IngredientMatch::new(drink, &bar);
+                if matched.missing.len() <= 1 {
+                    result.push(matched);
+                }
+            }
+        }
+        result.sort_by(|first, second| {
+            first
+                .missing
+                .len()
+                .cmp(&second.missing.len())
+                .then(second.used.len().cmp(&first.used.len()))
+                .then(first.drink.name.cmp(&second.drink.name))
+        });
+        let lang = Arc::new(lang);
+        Ok(result
+            .into_iter()
+            .map(|matched| LangMatch {
+                matched,
+                lang: lang.clone(),
+                units,
+            })
+            .collect())
+    }
+
+    pub async fn get_all_category(&self, lang: Lang) -> Result<Vec<LangList>, ErrorHandler> {
+        let result = self.source.all_categories().await?.ok_or(ErrorHandler {
+            msg: "Fail to get All.".to_string(),
+            ty: ErrorType::Unexpected,
+        })?;
         Ok(Self::to_lazy(result, Arc::new(lang)))
     }
 
     pub async fn find_by_category(
+        &self,
         name: &str,
         lang: Lang,
     ) -> Result<Option<Vec<LangLazyDrink>>, ErrorHandler> {
-        let result = Self::send_request::<LazyDrink>(SEARCH_BY_CATEGORY, Some(name))
+        let result = self
+            .source
+            .filter_by_category(&lang.names.category_key(name))
             .await?
             .map(|drinks| Self::to_lazy(drinks, Arc::new(lang)));
 
         Ok(result)
     }
-    pub fn to_lazy<F, T: ToLangDrink<F>>(drinks: Vec<F>, lang: Arc<Lang>) -> Vec<T> {
-        drinks
+    /// Taste built from `(drink id, weight)` pairs, drinks which are gone are skipped.
+    pub async fn taste(&self, history: &[(String, i64)]) -> Result<Taste, ErrorHandler> {
+        let mut drinks = Vec::with_capacity(history.len());
+        for (id, weight) in history {
+            if let Some(drink) = self.source.lookup_by_id(id).await? {
+                drinks.push((drink, *weight));
+            }
+        }
+        Ok(Taste::new(&drinks))
+    }
+
+    /// Drinks with the favourite ingredients and category, which are not in `seen`.
+    pub async fn recommend(
+        &self,
+        taste: &Taste,
+        seen: &[String],
+        limit: usize,
+        lang: Lang,
+        units: Units,
+    ) -> Result<Vec<LangRecommendation>, ErrorHandler> {
+        let mut ids: Vec<String> = vec![];
+        let mut candidates = vec![];
+        for name in taste.favorite_ingredients(TASTE_INGREDIENTS) {
+            candidates.extend(
+                self.source
+                    .filter_by_ingredient(&name)
+                    .await?
+                    .unwrap_or_default(),
+            );
+        }
+        if let Some(category) = taste.favorite_category() {
+            candidates.extend(
+                self.source
+                    .filter_by_category(&category)
+                    .await?
+                    .unwrap_or_default(),
+            );
+        }
+        for candidate in candidates {
+            if !seen.contains(&candidate.id) && !ids.contains(&candidate.id) {
+                ids.push(candidate.id);
+            }
+        }
+
+        let mut drinks = vec![];
+        for id in ids.iter().take(RECOMMEND_CANDIDATES) {
+            if let Some(drink) = self.source.lookup_by_id(id).await? {
+                drinks.push(drink);
+            }
+        }
+        let lang = Arc::new(lang);
+        Ok(taste
+            .rank(drinks)
             .into_iter()
-            .filter_map(|drink| match T::new(drink, lang.clone()) {
-                Err(_) => None,
-                Ok(t) => Some(t),
+            .take(limit)
+            .map(|recommendation| LangRecommendation {
+                recommendation,
+                lang: lang.clone(),
+                units,
             })
-            .collect::<Vec<T>>()
+            .collect())
     }
 
-    async fn send_request<T: DeserializeOwned>(
-        url: &str,
-        addition: Option<&str>,
-    ) -> Result<Option<Vec<T>>, ErrorHandler> {
-        let result = reqwest::get(format!("{}{}", url, addition.unwrap_or("")))
-            .await?
-            .bytes()
-            .await?;
-        if result.is_empty() {
-            Ok(None)
-        } else {
-            let result = serde_json::from_slice::<RawDrinkListSchema<T>>(result.as_ref()).unwrap();
-
-            if result.is_empty() {
-                Ok(None)
-            } else {
-                Ok(Some(result.drinks.ok_or(ErrorHandler {
-                    msg: "It should never happen.".to_string(),
-                    ty: ErrorType::Unexpected,
-                })?))
+    /// Ingredients of the drinks merged into one list, without the ones in the bar.
+    pub async fn shopping_list(
+        &self,
+        ids: &[String],
+        bar: &[String],
+        lang: Lang,
+        units: Units,
+    ) -> Result<LangShopping, ErrorHandler> {
+        let mut drinks = Vec::with_capacity(ids.len());
+        for id in ids {
+            if let Some(drink) = self.source.lookup_by_id(id).await? {
+                drinks.push(drink);
             }
         }
+        let mut groups = HashMap::new();
+        for (name, _) in drinks.iter().flat_map(|drink| drink.ingredients.iter()) {
+            let key = name.trim().to_lowercase();
+            if groups.contains_key(&key) {
+                continue;
+            }
+            let group = self
+                .source
+                .ingredients_by_name(name.trim())
+                .await?
+                .and_then(|ingredients| ingredients.into_iter().next())
+                .map(|ingredient| Group::from_ty(ingredient.ty.as_deref(), ingredient.alco))
+                .unwrap_or(Group::Other);
+            groups.insert(key, group);
+        }
+        let bar = bar
+            .iter()
+            .map(|name| lang.names.ingredient_key(name))
+            .collect::<Vec<String>>();
+        Ok(LangShopping {
+            list: ShoppingList::new(&drinks, &bar, &groups),
+            lang: Arc::new(lang),
+            units,
+        })
+    }
+
+    fn in_units(drinks: Vec<LangDrink>, units: Units) -> Vec<LangDrink> {
+        drinks
+            .into_iter()
+            .map(|drink| drink.in_units(units))
+            .collect()
+    }
+
+    pub fn to_lazy<F, T: ToLangDrink<F>>(drinks: Vec<F>, lang: Arc<Lang>) -> Vec<T> {
+        drinks
+            .into_iter()
+            .filter_map(|drink| T::new(drink, lang.clone()).ok())
+            .collect::<Vec<T>>()
     }
 }
 
 #[cfg(test)]
 pub mod tests {
+    use std::sync::Arc;
+
+    use crate::cocktails_api::schemas::measure::Units;
+    use crate::cocktails_api::schemas::shopping::Group;
     use crate::cocktails_api::services::coctail_service::DrinksService;
+    use crate::cocktails_api::services::local_source::LocalSource;
     use crate::localization::lang::Lang;
 
+    pub const FIXTURE: &str = "./dataset/fixture.json";
+
+    pub fn service() -> DrinksService {
+        DrinksService::new(Arc::new(
+            LocalSource::from_file(FIXTURE).expect("Fail to load fixture."),
+        ))
+    }
+
     #[tokio::test]
     async fn test_drink_by_name() {
-        let result = DrinksService::get_drink_by_name("Margarita", Lang::Ukr).await;
+        let result = service()
+            .get_drink_by_name("Margarita", Lang::new("uk"), Units::default())
+            .await;
 
-        assert!(result.is_ok())
+        assert!(result.is_ok());
+        assert_eq!(result.unwrap().unwrap()[0].drink.name, "Margarita");
     }
 
     #[tokio::test]
     async fn test_get_ingr_by_name() {
-        let result = DrinksService::get_ingredient_by_name("Vodka", Lang::Ukr).await;
-        assert!(result.is_ok())
+        let result = service()
+            .get_ingredient_by_name("Vodka", Lang::new("uk"))
+            .await;
+        assert!(result.unwrap().is_some())
     }
 
     #[tokio::test]
     async fn test_get_all_ingr() {
-        let result = DrinksService::get_all_ingredients(Lang::Ukr).await;
+        let result = service().get_all_ingredients(Lang::new("uk")).await;
         assert!(result.is_ok())
     }
 
     #[tokio::test]
     async fn test_find_by_ingr() {
-        let result = DrinksService::find_by_ingredient("Vodka", Lang::Ukr).await;
-        assert!(result.is_ok())
+        let result = service().find_by_ingredient("Vodka", Lang::new("uk")).await;
+        assert_eq!(result.unwrap().unwrap().len(), 2);
+        let result = service()
+            .find_by_ingredient("горілка", Lang::new("uk"))
+            .await;
+        assert_eq!(result.unwrap().unwrap().len(), 2)
+    }
+
+    #[tokio::test]
+    async fn test_find_by_ingredients() {
+        let names = vec!["Vodka".to_string(), "Lime juice".to_string()];
+        let result = service()
+            .find_by_ingredients(&names, 10, Lang::default(), Units::default())
+            .await
+            .unwrap();
+        assert_eq!(result[0].matched.drink.name, "Moscow Mule");
+        assert!(result[0]
+            .matched
+            .missing
+            .contains(&"Ginger ale".to_string()));
+    }
+
+    #[tokio::test]
+    async fn test_what_can_i_make() {
+        let bar = vec![
+            "Vodka".to_string(),
+            "Lime juice".to_string(),
+            "Dry Vermouth".to_string(),
+        ];
+        let result = service()
+            .what_can_i_make(&bar, Lang::default(), Units::default())
+            .await
+            .unwrap();
+        let names = result
+            .iter()
+            .map(|matched| matched.matched.drink.name.as_str())
+            .collect::<Vec<&str>>();
+        assert_eq!(names, vec!["Moscow Mule", "Vodka Martini"]);
+
+        let bar = vec!["Water".to_string(), "Lemon juice".to_string()];
+        let result = service()
+            .what_can_i_make(&bar, Lang::default(), Units::default())
+            .await
+            .unwrap();
+        assert_eq!(result[0].matched.drink.name, "Lemonade");
+        assert_eq!(result[0].matched.missing, vec!["Sugar"]);
+    }
+
+    #[tokio::test]
+    async fn test_recommend() {
+        let service = service();
+        let taste = service
+            .taste(&[("11000".to_string(), 3), ("11009".to_string(), -4)])
+            .await
+            .unwrap();
+        let seen = vec!["11000".to_string(), "11009".to_string()];
+        let result = service
+            .recommend(&taste, &seen, 5, Lang::default(), Units::default())
+            .await
+            .unwrap();
+        let names = result
+            .iter()
+            .map(|recommendation| recommendation.recommendation.drink.name.as_str())
+            .collect::<Vec<&str>>();
+        assert_eq!(names, vec!["Vodka Martini"]);
     }
 
     #[tokio::test]
     async fn test_get_all_category() {
-        let result = DrinksService::get_all_category(Lang::Ukr).await;
+        let result = service().get_all_category(Lang::new("uk")).await;
         assert!(result.is_ok())
     }
 
     #[tokio::test]
     async fn test_find_by_category() {
-        let result = DrinksService::find_by_category("Cocktail", Lang::Ukr).await;
-        assert!(result.is_ok())
+        let result = service()
+            .find_by_category("Cocktail", Lang::new("uk"))
+            .await;
+        assert_eq!(result.unwrap().unwrap().len(), 2)
+    }
+
+    #[tokio::test]
+    async fn test_lookup_by_id() {
+        let result = service()
+            .lookup_by_id("11007", Lang::default(), Units::Metric)
+            .await;
+        let drink = result.unwrap().unwrap();
+        assert_eq!(drink.drink.name, "Margarita");
+        assert!(drink.to_string().contains("Tequila 44 ml"));
+    }
+
+    #[tokio::test]
+    async fn test_search_by_first_letter() {
+        let result = service()
+            .search_by_first_letter(&'M', Lang::new("uk"), Units::default())
+            .await;
+        assert_eq!(result.unwrap().unwrap().len(), 3);
+        let result = service()
+            .search_by_first_letter(&'Z', Lang::new("uk"), Units::default())
+            .await;
+        assert!(result.unwrap().is_none())
+    }
+
+    #[tokio::test]
+    async fn test_random_drink() {
+        let result = service()
+            .random_drink(Lang::new("uk"), Units::default())
+            .await;
+        assert!(result.unwrap().unwrap().drink.id.is_some());
+    }
+
+    #[tokio::test]
+    async fn test_shopping_list() {
+        let ids = vec!["11007".to_string(), "11009".to_string()];
+        let shopping = service()
+            .shopping_list(
+                &ids,
+                &["Vodka".to_string()],
+                Lang::default(),
+                Units::default(),
+            )
+            .await
+            .unwrap();
+        let lime = &shopping.list.items[3];
+        assert_eq!(
+            (lime.name.as_str(), lime.group),
+            ("Lime juice", Group::Mixer)
+        );
+        assert_eq!(lime.amount(Units::Original), "3 oz");
+        assert_eq!(shopping.list.items[1].group, Group::Liqueur);
+        assert_eq!(shopping.list.in_bar, vec!["Vodka".to_string()]);
     }
 }
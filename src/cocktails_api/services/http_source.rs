@@ -0,0 +1,161 @@
+use async_trait::async_trait;
+use serde::de::DeserializeOwned;
+use serde_json::Value;
+
+use crate::cocktails_api::schemas::drink::{Drink, LangDrink, LazyDrink};
+use crate::cocktails_api::schemas::ingredient::Ingredient;
+use crate::cocktails_api::schemas::lists::List;
+use crate::cocktails_api::schemas::RawDrinkListSchema;
+use crate::cocktails_api::services::source::CocktailSource;
+use crate::config::Env;
+use crate::error::error_handler::{ErrorHandler, ErrorType};
+
+pub const DRINK_BY_NAME: &str = "search.php?s=";
+pub const INGREDIENT_BY_NAME: &str = "search.php?i=";
+pub const LOOKUP_BY_ID: &str = "lookup.php?i=";
+pub const ALL_INGREDIENTS: &str = "list.php?i=list";
+pub const ALL_CATEGORY: &str = "list.php?c=list";
+pub const ALL_GLASSES: &str = "list.php?g=list";
+pub const SEARCH_BY_INGREDIENT: &str = "filter.php?i=";
+pub const SEARCH_BY_CATEGORY: &str = "filter.php?c=";
+pub const SEARCH_BY_FIRST_LATTER: &str = "search.php?f=";
+pub const RANDOM: &str = "random.php";
+
+pub const DEFAULT_BASE_URL: &str = "https://www.thecocktaildb.com/api/json/v1";
+pub const DEFAULT_API_KEY: &str = "1";
+
+/// thecocktaildb.com compatible backend.
+pub struct HttpSource {
+    client: reqwest::Client,
+    base_url: String,
+    api_key: String,
+}
+
+impl HttpSource {
+    pub fn new(base_url: &str, api_key: &str) -> Self {
+        Self {
+            client: reqwest::Client::new(),
+            base_url: base_url.trim_end_matches('/').to_string(),
+            api_key: api_key.to_string(),
+        }
+    }
+
+    pub fn from_env(env: &Env) -> Self {
+        Self::new(&env.cocktail_api_url, &env.cocktail_api_key)
+    }
+
+    pub async fn fetch<T: DeserializeOwned>(
+        &self,
+        endpoint: &str,
+        addition: Option<&str>,
+    ) -> Result<Option<Vec<T>>, ErrorHandler> {
+        let url = format!(
+            "{}/{}/{}{}",
+            self.base_url,
+            self.api_key,
+            endpoint,
+            addition.unwrap_or("")
+        );
+        let result = self.client.get(url).send().await?.bytes().await?;
+        if result.is_empty() {
+            Ok(None)
+        } else {
+            let result = serde_json::from_slice::<RawDrinkListSchema<T>>(result.as_ref())?;
+
+            if result.is_empty() {
+                Ok(None)
+            } else {
+                Ok(Some(result.drinks.ok_or(ErrorHandler {
+                    msg: "It should never happen.".to_string(),
+                    ty: ErrorType::Unexpected,
+                })?))
+            }
+        }
+    }
+}
+
+impl Default for HttpSource {
+    fn default() -> Self {
+        Self::new(DEFAULT_BASE_URL, DEFAULT_API_KEY)
+    }
+}
+
+#[async_trait]
+impl CocktailSource for HttpSource {
+    async fn drinks_by_name(&self, name: &str) -> Result<Option<Vec<Drink>>, ErrorHandler> {
+        if let Some(drinks) = self.fetch::<Value>(DRINK_BY_NAME, Some(name)).await? {
+            let mut vec_drinks = Vec::with_capacity(drinks.len());
+            for drink in drinks {
+                vec_drinks.push(LangDrink::drink_from_value(&drink)?);
+            }
+            Ok(Some(vec_drinks))
+        } else {
+            Ok(None)
+        }
+    }
+
+    async fn ingredients_by_name(
+        &self,
+        name: &str,
+    ) -> Result<Option<Vec<Ingredient>>, ErrorHandler> {
+        self.fetch::<Ingredient>(INGREDIENT_BY_NAME, Some(name))
+            .await
+    }
+
+    async fn drinks_by_first_letter(
+        &self,
+        letter: &char,
+    ) -> Result<Option<Vec<Drink>>, ErrorHandler> {
+        let result = self
+            .fetch::<Value>(SEARCH_BY_FIRST_LATTER, Some(&format!("{}", letter)))
+            .await?
+            .map(|values| {
+                values
+                    .iter()
+                    .filter_map(|value| LangDrink::drink_from_value(value).ok())
+                    .collect::<Vec<Drink>>()
+            });
+        Ok(result)
+    }
+
+    async fn lookup_by_id(&self, id: &str) -> Result<Option<Drink>, ErrorHandler> {
+        match self.fetch::<Value>(LOOKUP_BY_ID, Some(id)).await? {
+            Some(drinks) => match drinks.first() {
+                Some(drink) => Ok(Some(LangDrink::drink_from_value(drink)?)),
+                None => Ok(None),
+            },
+            None => Ok(None),
+        }
+    }
+
+    async fn random_drink(&self) -> Result<Option<Drink>, ErrorHandler> {
+        match self.fetch::<Value>(RANDOM, None).await? {
+            Some(drinks) => match drinks.first() {
+                Some(drink) => Ok(Some(LangDrink::drink_from_value(drink)?)),
+                None => Ok(None),
+            },
+            None => Ok(None),
+        }
+    }
+
+    async fn all_ingredients(&self) -> Result<Option<Vec<List>>, ErrorHandler> {
+        self.fetch::<List>(ALL_INGREDIENTS, None).await
+    }
+
+    async fn all_categories(&self) -> Result<Option<Vec<List>>, ErrorHandler> {
+        self.fetch::<List>(ALL_CATEGORY, None).await
+    }
+
+    async fn filter_by_ingredient(
+        &self,
+        name: &str,
+    ) -> Result<Option<Vec<LazyDrink>>, ErrorHandler> {
+        self.fetch::<LazyDrink>(SEARCH_BY_INGREDIENT, Some(name))
+            .await
+    }
+
+    async fn filter_by_category(&self, name: &str) -> Result<Option<Vec<LazyDrink>>, ErrorHandler> {
+        self.fetch::<LazyDrink>(SEARCH_BY_CATEGORY, Some(name))
+            .await
+    }
+}
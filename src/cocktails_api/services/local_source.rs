@@ -0,0 +1,161 @@
+use async_trait::async_trait;
+
+use crate::cocktails_api::schemas::dataset::Dataset;
+use crate::cocktails_api::schemas::drink::{Drink, LazyDrink};
+use crate::cocktails_api::schemas::ingredient::Ingredient;
+use crate::cocktails_api::schemas::lists::List;
+use crate::cocktails_api::services::source::CocktailSource;
+use crate::error::error_handler::{ErrorHandler, ErrorType};
+use crate::utils::helpers::random_num_in_range;
+
+/// Offline backend, answers every query from a `Dataset` loaded into memory.
+pub struct LocalSource {
+    dataset: Dataset,
+}
+
+impl LocalSource {
+    pub fn new(dataset: Dataset) -> Self {
+        Self { dataset }
+    }
+
+    pub fn from_file(path: &str) -> Result<Self, ErrorHandler> {
+        let bytes = std::fs::read(path).map_err(|err| ErrorHandler {
+            msg: format!("Fail to read dataset {}: {}", path, err),
+            ty: ErrorType::File,
+        })?;
+        Ok(Self::new(serde_json::from_slice::<Dataset>(&bytes)?))
+    }
+
+    fn drinks_where<F>(&self, filter: F) -> Option<Vec<Drink>>
+    where
+        F: Fn(&Drink) -> bool,
+    {
+        Self::not_empty(
+            self.dataset
+                .drinks
+                .iter()
+                .filter(|drink| filter(drink))
+                .cloned()
+                .collect(),
+        )
+    }
+
+    fn to_lazy_drinks(drinks: Option<Vec<Drink>>) -> Option<Vec<LazyDrink>> {
+        drinks.map(|drinks| {
+            drinks
+                .into_iter()
+                .map(|drink| LazyDrink {
+                    id: drink.id.unwrap_or_default(),
+                    name: drink.name,
+                    image_url: drink.image.unwrap_or_default(),
+                })
+                .collect()
+        })
+    }
+
+    fn to_list(names: &[String]) -> Option<Vec<List>> {
+        Self::not_empty(
+            names
+                .iter()
+                .map(|name| List { name: name.clone() })
+                .collect(),
+        )
+    }
+
+    fn not_empty<T>(vec: Vec<T>) -> Option<Vec<T>> {
+        match vec.is_empty() {
+            true => None,
+            false => Some(vec),
+        }
+    }
+}
+
+#[async_trait]
+impl CocktailSource for LocalSource {
+    async fn drinks_by_name(&self, name: &str) -> Result<Option<Vec<Drink>>, ErrorHandler> {
+        let name = name.to_lowercase();
+        Ok(self.drinks_where(|drink| drink.name.to_lowercase().contains(&name)))
+    }
+
+    async fn ingredients_by_name(
+        &self,
+        name: &str,
+    ) -> Result<Option<Vec<Ingredient>>, ErrorHandler> {
+        Ok(Self::not_empty(
+            self.dataset
+                .ingredients
+                .iter()
+                .filter(|ingredient| ingredient.name.eq_ignore_ascii_case(name))
+                .cloned()
+                .collect(),
+        ))
+    }
+
+    async fn drinks_by_first_letter(
+        &self,
+        letter: &char,
+    ) -> Result<Option<Vec<Drink>>, ErrorHandler> {
+        let letter = letter.to_ascii_lowercase();
+        Ok(self.drinks_where(|drink| {
+            drink
+                .name
+                .chars()
+                .next()
+                .map(|first| first.to_ascii_lowercase() == letter)
+                .unwrap_or(false)
+        }))
+    }
+
+    async fn lookup_by_id(&self, id: &str) -> Result<Option<Drink>, ErrorHandler> {
+        Ok(self
+            .dataset
+            .drinks
+            .iter()
+            .find(|drink| drink.id.as_deref() == Some(id))
+            .cloned())
+    }
+
+    async fn random_drink(&self) -> Result<Option<Drink>, ErrorHandler> {
+        let drinks = &self.dataset.drinks;
+        match drinks.is_empty() {
+            true => Ok(None),
+            false => Ok(Some(drinks[random_num_in_range(0, drinks.len())].clone())),
+        }
+    }
+
+    async fn all_ingredients(&self) -> Result<Option<Vec<List>>, ErrorHandler> {
+        let names = self
+            .dataset
+            .ingredients
+            .iter()
+            .map(|ingredient| ingredient.name.clone())
+            .collect::<Vec<String>>();
+        Ok(Self::to_list(&names))
+    }
+
+    async fn all_categories(&self) -> Result<Option<Vec<List>>, ErrorHandler> {
+        Ok(Self::to_list(&self.dataset.categories))
+    }
+
+    async fn filter_by_ingredient(
+        &self,
+        name: &str,
+    ) -> Result<Option<Vec<LazyDrink>>, ErrorHandler> {
+        Ok(Self::to_lazy_drinks(self.drinks_where(|drink| {
+            drink
+                .ingredients
+                .iter()
+                .any(|(ingredient, _)| ingredient.eq_ignore_ascii_case(name))
+        })))
+    }
+
+    async fn filter_by_category(&self, name: &str) -> Result<Option<Vec<LazyDrink>>, ErrorHandler> {
+        Ok(Self::to_lazy_drinks(self.drinks_where(|drink| {
+            drink
+                .category
+                .as_ref()
+                .map(|category| category.eq_ignore_ascii_case(name))
+                .unwrap_or(false)
+        })))
+    }
+}
@@ -1 +1,5 @@
+pub mod cached_source;
 pub mod coctail_service;
+pub mod http_source;
+pub mod local_source;
+pub mod source;
@@ -0,0 +1,38 @@
+use async_trait::async_trait;
+
+use crate::cocktails_api::schemas::drink::{Drink, LazyDrink};
+use crate::cocktails_api::schemas::ingredient::Ingredient;
+use crate::cocktails_api::schemas::lists::List;
+use crate::error::error_handler::ErrorHandler;
+
+/// Backend which provides raw cocktail data.
+/// `Ok(None)` means that the source found nothing for the query.
+#[async_trait]
+pub trait CocktailSource: Send + Sync {
+    async fn drinks_by_name(&self, name: &str) -> Result<Option<Vec<Drink>>, ErrorHandler>;
+
+    async fn ingredients_by_name(
+        &self,
+        name: &str,
+    ) -> Result<Option<Vec<Ingredient>>, ErrorHandler>;
+
+    async fn drinks_by_first_letter(
+        &self,
+        letter: &char,
+    ) -> Result<Option<Vec<Drink>>, ErrorHandler>;
+
+    async fn lookup_by_id(&self, id: &str) -> Result<Option<Drink>, ErrorHandler>;
+
+    async fn random_drink(&self) -> Result<Option<Drink>, ErrorHandler>;
+
+    async fn all_ingredients(&self) -> Result<Option<Vec<List>>, ErrorHandler>;
+
+    async fn all_categories(&self) -> Result<Option<Vec<List>>, ErrorHandler>;
+
+    async fn filter_by_ingredient(
+        &self,
+        name: &str,
+    ) -> Result<Option<Vec<LazyDrink>>, ErrorHandler>;
+
+    async fn filter_by_category(&self, name: &str) -> Result<Option<Vec<LazyDrink>>, ErrorHandler>;
+}
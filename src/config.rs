@@ -2,6 +2,8 @@ extern crate envy;
 
 use serde::Deserialize;
 
+use crate::cocktails_api::services::http_source::{DEFAULT_API_KEY, DEFAULT_BASE_URL};
+
 #[derive(Deserialize, Debug)]
 pub struct Env {
     pub bot_id: String,
@@ -9,4 +11,30 @@ pub struct Env {
     pub workers_number: usize,
     pub blocking_treads: usize,
     pub db_path: String,
+    #[serde(default = "default_api_url")]
+    pub cocktail_api_url: String,
+    #[serde(default = "default_api_key")]
+    pub cocktail_api_key: String,
+    /// Path to a local dataset, if set the bot works without thecocktaildb.com.
+    pub dataset_path: Option<String>,
+    #[serde(default = "default_cache_size")]
+    pub cache_size: usize,
+    /// Keeps cached responses in the `db_path` database.
+    #[serde(default)]
+    pub persist_cache: bool,
+    /// Telegram user ids allowed to use the admin commands, `ADMIN_IDS=1,2`.
+    #[serde(default)]
+    pub admin_ids: Vec<u64>,
+}
+
+fn default_api_url() -> String {
+    DEFAULT_BASE_URL.to_string()
+}
+
+fn default_api_key() -> String {
+    DEFAULT_API_KEY.to_string()
+}
+
+fn default_cache_size() -> usize {
+    512
 }
@@ -69,3 +69,12 @@ impl From<url::ParseError> for ErrorHandler {
         }
     }
 }
+
+impl From<sqlx::Error> for ErrorHandler {
+    fn from(err: sqlx::Error) -> Self {
+        Self {
+            msg: err.to_string(),
+            ty: ErrorType::Database,
+        }
+    }
+}
@@ -0,0 +1,336 @@
+use itertools::Itertools;
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+use serde::{Deserialize, Serialize};
+
+use crate::cocktails_api::schemas::drink::{Drink, LangDrink};
+use crate::cocktails_api::schemas::ToLangDrink;
+use crate::game::questions::{Answer, Question, Topic};
+
+/// More values of a topic are not offered, "either" keeps them.
+const MAX_OPTIONS: usize = 4;
+
+pub trait GameDrink {
+    fn drink(&self) -> &Drink;
+}
+
+impl GameDrink for Drink {
+    fn drink(&self) -> &Drink {
+        self
+    }
+}
+
+impl GameDrink for LangDrink {
+    fn drink(&self) -> &Drink {
+        self.get_drink()
+    }
+}
+
+pub enum Step<'a, T> {
+    Ask(&'a Question),
+    Done(&'a T),
+}
+
+/// "Drink for you" game without any I/O.
+///
+/// The next question is the one which splits the pool best, an answer never
+/// drops the last drink and every topic is asked at most twice, so the game
+/// always ends with a drink. The rng is derived from the seed and the round,
+/// which keeps the game serializable and replayable.
+#[derive(Clone, Serialize, Deserialize, Debug)]
+pub struct Game<T> {
+    seed: u64,
+    round: u64,
+    pool: Vec<T>,
+    question: Option<Question>,
+    answered: Vec<Topic>,
+    skipped: Vec<Topic>,
+}
+
+impl<T: GameDrink> Game<T> {
+    /// `None` for an empty pool.
+    pub fn new(pool: Vec<T>, seed: u64) -> Option<Self> {
+        if pool.is_empty() {
+            return None;
+        }
+        let mut game = Self {
+            seed,
+            round: 0,
+            pool,
+            question: None,
+            answered: vec![],
+            skipped: vec![],
+        };
+        game.question = game.next_question();
+        Some(game)
+    }
+
+    pub fn step(&self) -> Step<'_, T> {
+        match &self.question {
+            Some(question) => Step::Ask(question),
+            None => Step::Done(&self.pool[self.rng().gen_range(0..self.pool.len())]),
+        }
+    }
+
+    /// Returns `false` when the answer does not fit the current question.
+    pub fn choose(&mut self, answer: Answer) -> bool {
+        let question = match &self.question {
+            Some(question) => question,
+            None => return false,
+        };
+        let topic = question.topic;
+        match answer {
+            Answer::Option(num) => {
+                let value = match question.options.get(num) {
+                    Some(value) => value.clone(),
+                    None => return false,
+                };
+                self.pool
+                    .retain(|drink| topic.value(drink.drink()).is_none_or(|have| have == value));
+                self.answer(topic);
+            }
+            Answer::Either => self.answer(topic),
+            Answer::Skip if !self.skipped.contains(&topic) => self.skipped.push(topic),
+            Answer::Skip => self.answer(topic),
+        }
+        self.round += 1;
+        self.question = self.next_question();
+        true
+    }
+
+    #[cfg(test)]
+    pub fn pool(&self) -> &[T] {
+        &self.pool
+    }
+
+    fn answer(&mut self, topic: Topic) {
+        self.skipped.retain(|skipped| *skipped != topic);
+        self.answered.push(topic);
+    }
+
+    /// Skipped topics come back once nothing else splits the pool.
+    fn next_question(&self) -> Option<Question> {
+        let fresh = Topic::ALL
+            .iter()
+            .filter(|topic| !self.answered.contains(topic) && !self.skipped.contains(topic));
+        self.best_question(fresh)
+            .or_else(|| self.best_question(self.skipped.iter()))
+    }
+
+    /// Greedy choice by information gain, the earlier topic wins a tie.
+    fn best_question<'a>(&self, topics: impl Iterator<Item = &'a Topic>) -> Option<Question> {
+        let mut best: Option<(f64, Question)> = None;
+        for topic in topics {
+            if let Some((gain, question)) = self.question(*topic) {
+                if best.as_ref().is_none_or(|(best, _)| gain > *best) {
+                    best = Some((gain, question));
+                }
+            }
+        }
+        best.map(|(_, question)| question)
+    }
+
+    /// Entropy of the answers, drinks which fit any answer make a question less useful.
+    fn question(&self, topic: Topic) -> Option<(f64, Question)> {
+        let counts = self
+            .pool
+            .iter()
+            .filter_map(|drink| topic.value(drink.drink()))
+            .counts();
+        if counts.len() < 2 {
+            return None;
+        }
+        let known = counts.values().sum::<usize>() as f64;
+        // Summed in a fixed order, so the same pool always gets the same gain.
+        let entropy = counts
+            .values()
+            .sorted()
+            .map(|&count| {
+                let part = count as f64 / known;
+                -part * part.log2()
+            })
+            .sum::<f64>();
+        let options = counts
+            .into_iter()
+            .sorted_by(|(first, first_count), (second, second_count)| {
+                second_count.cmp(first_count).then(first.cmp(second))
+            })
+            .take(MAX_OPTIONS)
+            .map(|(value, _)| value)
+            .collect();
+        Some((
+            entropy * known / self.pool.len() as f64,
+            Question { topic, options },
+        ))
+    }
+
+    fn rng(&self) -> StdRng {
+        StdRng::seed_from_u64(self.seed.wrapping_add(self.round))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use proptest::prelude::*;
+
+    use crate::cocktails_api::schemas::drink::Drink;
+    use crate::game::engine::{Game, Step};
+    use crate::game::questions::{Answer, Question, Topic};
+    use crate::utils::testing::DrinkBuilder;
+
+    fn drink(
+        num: usize,
+        alco: bool,
+        category: Option<u8>,
+        glass: Option<u8>,
+        ingredient: Option<u8>,
+    ) -> Drink {
+        let mut drink = DrinkBuilder::new(&format!("Drink {}", num))
+            .id(&num.to_string())
+            .alco(alco);
+        if let Some(category) = category {
+            drink = drink.category(&format!("Category {}", category));
+        }
+        if let Some(glass) = glass {
+            drink = drink.glass(&format!("Glass {}", glass));
+        }
+        if let Some(ingredient) = ingredient {
+            drink = drink.ingredients(&[&format!("Ingredient {}", ingredient)]);
+        }
+        drink.build()
+    }
+
+    fn pool() -> impl Strategy<Value = Vec<Drink>> {
+        let drink_strategy = (
+            any::<bool>(),
+            prop::option::of(0u8..6),
+            prop::option::of(0u8..3),
+            prop::option::of(0u8..4),
+        );
+        prop::collection::vec(drink_strategy, 0..20).prop_map(|drinks| {
+            drinks
+                .into_iter()
+                .enumerate()
+                .map(|(num, (alco, category, glass, ingredient))| {
+                    drink(num, alco, category, glass, ingredient)
+                })
+                .collect()
+        })
+    }
+
+    fn to_answer(num: u8, question: &Question) -> Answer {
+        match num % 6 {
+            4 => Answer::Either,
+            5 => Answer::Skip,
+            num => Answer::Option(num as usize % question.options.len()),
+        }
+    }
+
+    /// Plays the game to the end, returns the asked questions and the result.
+    fn play(mut game: Game<Drink>, answers: &[u8]) -> (Vec<Question>, Drink) {
+        let mut asked = vec![];
+        let mut answers = answers.iter().cycle();
+        loop {
+            let question = match game.step() {
+                Step::Ask(question) => question.clone(),
+                Step::Done(drink) => return (asked, drink.clone()),
+            };
+            let answer = to_answer(*answers.next().unwrap_or(&0), &question);
+            assert!(game.choose(answer));
+            assert!(!game.pool().is_empty());
+            if let Answer::Option(num) = answer {
+                assert!(game.pool().iter().all(|drink| question
+                    .topic
+                    .value(drink)
+                    .is_none_or(|value| value == question.options[num])));
+            }
+            asked.push(question);
+        }
+    }
+
+    #[test]
+    fn test_best_split_first() {
+        let pool = vec![
+            drink(0, true, Some(0), Some(0), None),
+            drink(1, false, Some(0), Some(1), None),
+            drink(2, true, Some(0), Some(2), None),
+            drink(3, false, Some(1), Some(3), None),
+        ];
+        let mut game = Game::new(pool, 7).unwrap();
+        match game.step() {
+            Step::Ask(question) => {
+                assert_eq!(question.topic, Topic::Glass);
+                assert_eq!(question.options.len(), 4);
+            }
+            Step::Done(_) => panic!("The pool can be split."),
+        }
+        assert!(!game.choose(Answer::Option(4)));
+        assert!(game.choose(Answer::Option(1)));
+        assert_eq!(game.pool().len(), 1);
+        assert!(matches!(game.step(), Step::Done(_)));
+    }
+
+    #[test]
+    fn test_skip_comes_back() {
+        let pool = vec![
+            drink(0, true, None, None, None),
+            drink(1, false, None, None, None),
+        ];
+        let mut game = Game::new(pool, 7).unwrap();
+        assert!(game.choose(Answer::Skip));
+        assert!(matches!(game.step(), Step::Ask(question) if question.topic == Topic::Alcohol));
+        assert!(game.choose(Answer::Skip));
+        assert!(matches!(game.step(), Step::Done(_)));
+        assert_eq!(game.pool().len(), 2);
+    }
+
+    proptest! {
+        #[test]
+        fn test_always_ends_with_drink(pool in pool(), seed: u64, answers: Vec<u8>) {
+            match Game::new(pool.clone(), seed) {
+                Some(game) => {
+                    let (asked, result) = play(game, &answers);
+                    prop_assert!(asked.len() <= Topic::ALL.len() * 2);
+                    prop_assert!(pool.contains(&result));
+                }
+                None => prop_assert!(pool.is_empty()),
+            }
+        }
+
+        #[test]
+        fn test_same_seed_same_game(pool in pool(), seed: u64, answers: Vec<u8>) {
+            let first = Game::new(pool.clone(), seed).map(|game| play(game, &answers));
+            let second = Game::new(pool, seed).map(|game| play(game, &answers));
+            prop_assert_eq!(first, second);
+        }
+
+        #[test]
+        fn test_ingredient_question(
+            ingredients in prop::collection::vec(0u8..4, 1..20),
+            seed: u64,
+            num: usize,
+        ) {
+            let pool = ingredients
+                .iter()
+                .enumerate()
+                .map(|(id, ingredient)| drink(id, false, None, None, Some(*ingredient)))
+                .collect::<Vec<Drink>>();
+            let mut game = Game::new(pool, seed).unwrap();
+            let question = match game.step() {
+                Step::Ask(question) => question.clone(),
+                Step::Done(_) => {
+                    let first = ingredients[0];
+                    prop_assert!(ingredients.iter().all(|&ingredient| ingredient == first));
+                    return Ok(());
+                }
+            };
+            prop_assert_eq!(question.topic, Topic::Ingredient);
+            let chosen = num % question.options.len();
+            prop_assert!(game.choose(Answer::Option(chosen)));
+            prop_assert!(game
+                .pool()
+                .iter()
+                .all(|drink| drink.ingredients[0].0 == question.options[chosen]));
+        }
+    }
+}
@@ -0,0 +1,189 @@
+use std::fmt::{Display, Formatter};
+
+use serde::{Deserialize, Serialize};
+
+use crate::cocktails_api::schemas::drink::Drink;
+use crate::error::error_handler::{ErrorHandler, ErrorType};
+
+const SPIRITS: [&str; 14] = [
+    "vodka",
+    "rum",
+    "gin",
+    "tequila",
+    "whiskey",
+    "whisky",
+    "bourbon",
+    "brandy",
+    "cognac",
+    "scotch",
+    "absinthe",
+    "mezcal",
+    "pisco",
+    "everclear",
+];
+const SWEETENERS: [&str; 9] = [
+    "syrup",
+    "sugar",
+    "liqueur",
+    "grenadine",
+    "honey",
+    "cream",
+    "chocolate",
+    "schweppes",
+    "cola",
+];
+
+pub const ALCO: &str = "alco";
+pub const NON_ALCO: &str = "non_alco";
+pub const STRONG: &str = "strong";
+pub const LIGHT: &str = "light";
+pub const SWEET: &str = "sweet";
+pub const DRY: &str = "dry";
+
+#[derive(Clone, Copy, Serialize, Deserialize, Debug, PartialEq, Eq)]
+pub enum Topic {
+    Alcohol,
+    Category,
+    Glass,
+    /// The main ingredient, recipes list it first.
+    Ingredient,
+    Strength,
+    Sweetness,
+}
+
+impl Topic {
+    pub const ALL: [Topic; 6] = [
+        Topic::Alcohol,
+        Topic::Category,
+        Topic::Glass,
+        Topic::Ingredient,
+        Topic::Strength,
+        Topic::Sweetness,
+    ];
+
+    /// `None` when the drink fits any answer.
+    pub fn value(&self, drink: &Drink) -> Option<String> {
+        match self {
+            Topic::Alcohol => Some(if drink.alco { ALCO } else { NON_ALCO }.to_string()),
+            Topic::Category => drink.category.clone(),
+            Topic::Glass => drink.glass.clone(),
+            Topic::Ingredient => drink.ingredients.first().map(|(name, _)| name.clone()),
+            Topic::Strength => match drink.alco {
+                true => Some(Self::strength(drink).to_string()),
+                false => None,
+            },
+            Topic::Sweetness => Some(Self::sweetness(drink).to_string()),
+        }
+    }
+
+    /// A drink is strong when spirits make up at least half of it.
+    fn strength(drink: &Drink) -> &'static str {
+        let spirits = Self::count(drink, &SPIRITS);
+        match spirits > 0 && spirits * 2 >= drink.ingredients.len() {
+            true => STRONG,
+            false => LIGHT,
+        }
+    }
+
+    fn sweetness(drink: &Drink) -> &'static str {
+        match Self::count(drink, &SWEETENERS) > 0 {
+            true => SWEET,
+            false => DRY,
+        }
+    }
+
+    /// Whole words only, "Ginger ale" is not gin.
+    fn count(drink: &Drink, words: &[&str]) -> usize {
+        drink
+            .ingredients
+            .iter()
+            .filter(|(name, _)| {
+                name.to_lowercase()
+                    .split_whitespace()
+                    .any(|word| words.contains(&word))
+            })
+            .count()
+    }
+}
+
+#[derive(Clone, Copy, Serialize, Deserialize, Debug, PartialEq, Eq)]
+pub enum Answer {
+    Option(usize),
+    /// Any option is fine.
+    Either,
+    /// Ask something else first.
+    Skip,
+}
+
+impl Display for Answer {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Answer::Option(num) => write!(f, "{}", num),
+            Answer::Either => write!(f, "either"),
+            Answer::Skip => write!(f, "skip"),
+        }
+    }
+}
+
+impl TryFrom<&str> for Answer {
+    type Error = ErrorHandler;
+
+    fn try_from(value: &str) -> Result<Self, Self::Error> {
+        match value {
+            "either" => Ok(Answer::Either),
+            "skip" => Ok(Answer::Skip),
+            _ => value.parse().map(Answer::Option).map_err(|_| ErrorHandler {
+                msg: format!("Wrong answer: {}", value),
+                ty: ErrorType::Parse,
+            }),
+        }
+    }
+}
+
+#[derive(Clone, Serialize, Deserialize, Debug, PartialEq)]
+pub struct Question {
+    pub topic: Topic,
+    /// Values of the topic, the most common first.
+    pub options: Vec<String>,
+}
+
+#[cfg(test)]
+mod test {
+    use crate::cocktails_api::schemas::drink::Drink;
+    use crate::game::questions::{Answer, Topic, DRY, LIGHT, STRONG, SWEET};
+    use crate::utils::testing::DrinkBuilder;
+
+    fn drink(alco: bool, ingredients: &[&str]) -> Drink {
+        DrinkBuilder::new("Drink")
+            .alco(alco)
+            .ingredients(ingredients)
+            .build()
+    }
+
+    #[test]
+    fn test_values() {
+        let martini = drink(true, &["Gin", "Dry Vermouth"]);
+        let mule = drink(true, &["Vodka", "Ginger ale", "Lime"]);
+        let daiquiri = drink(true, &["Light rum", "Lime juice", "Sugar syrup"]);
+        let lemonade = drink(false, &["Lemon", "Sugar", "Water"]);
+        assert_eq!(Topic::Strength.value(&martini).unwrap(), STRONG);
+        assert_eq!(Topic::Strength.value(&daiquiri).unwrap(), LIGHT);
+        assert_eq!(Topic::Strength.value(&mule).unwrap(), LIGHT);
+        assert_eq!(Topic::Strength.value(&lemonade), None);
+        assert_eq!(Topic::Sweetness.value(&martini).unwrap(), DRY);
+        assert_eq!(Topic::Sweetness.value(&lemonade).unwrap(), SWEET);
+        assert_eq!(Topic::Ingredient.value(&mule).unwrap(), "Vodka");
+        assert_eq!(Topic::Ingredient.value(&drink(true, &[])), None);
+    }
+
+    #[test]
+    fn test_answer() {
+        for answer in [Answer::Option(3), Answer::Either, Answer::Skip] {
+            assert_eq!(
+                Answer::try_from(answer.to_string().as_str()).unwrap(),
+                answer
+            );
+        }
+        assert!(Answer::try_from("Vodka").is_err());
+    }
+}
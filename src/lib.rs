@@ -1,9 +1,30 @@
+use std::sync::Arc;
+
+use crate::cocktails_api::services::cached_source::CachedSource;
+use crate::cocktails_api::services::coctail_service::DrinksService;
+use crate::cocktails_api::services::http_source::HttpSource;
+use crate::cocktails_api::services::local_source::LocalSource;
+use crate::cocktails_api::services::source::CocktailSource;
 use crate::config::Env;
 use crate::error::error_handler::{ErrorHandler, ErrorType};
+use crate::storage::bans::BanStore;
+use crate::storage::feedback::{FeedbackStore, SUGGESTIONS_PATH};
+use crate::storage::history::HistoryStore;
+use crate::storage::profiles::ProfileStore;
+use crate::storage::stats::StatsStore;
+use crate::storage::subscriptions::SubscriptionStore;
+use crate::storage::votes::VoteStore;
 use crate::telegramm::buttons::callback_handler::CallBackHandler;
-use crate::telegramm::commands::command::StartCommands;
+use crate::telegramm::buttons::inline::CallbackData;
+use crate::telegramm::commands::admin::{AdminHandler, Admins};
+use crate::telegramm::commands::command::{AdminCommands, StartCommands};
+use crate::telegramm::commands::daily::DailyHandler;
 use crate::telegramm::commands::func::CommandsHandler;
+use crate::telegramm::commands::scale::ScaleHandler;
+use crate::telegramm::commands::vote::VoteHandler;
+use crate::telegramm::messages::group::GroupHandler;
 use crate::telegramm::messages::message_handler::MessageHandler;
+use crate::telegramm::queries::inline_query::InlineQueryHandler;
 use crate::telegramm::state::State;
 use teloxide::dispatching::dialogue::serializer::Json;
 use teloxide::dispatching::dialogue::ErasedStorage;
@@ -11,17 +32,18 @@ use teloxide::dispatching::dialogue::SqliteStorage;
 use teloxide::dispatching::dialogue::Storage;
 use teloxide::dispatching::{dialogue, Dispatcher, UpdateFilterExt, UpdateHandler};
 use teloxide::dptree::case;
-use teloxide::prelude::{RequesterExt, Update};
+use teloxide::prelude::{CallbackQuery, Message, RequesterExt, Update};
 use teloxide::{dptree, Bot};
 
-mod cocktails_api;
+pub mod cocktails_api;
 pub mod config;
-mod error;
+pub mod error;
+mod game;
 mod localization;
+mod storage;
 mod telegramm;
 mod utils;
 
-
 pub struct TelegrammBuilder;
 
 impl TelegrammBuilder {
@@ -39,10 +61,10 @@ impl TelegrammBuilder {
         rt.block_on(async move {
             if let Err(error) = Self::build(env).await {
                 if error.is_critical() {
-                    log::error!("Service critical error! Error msg: {:?}", error);
+                    log::error!("Service critical error! Error msg: {}", error.msg);
                     sx.send(0).expect("Sender failed...");
                 } else {
-                    log::error!("Error msg: {:?}", error);
+                    log::error!("Error msg: {}", error.msg);
                 }
             }
         });
@@ -63,7 +85,7 @@ impl TelegrammBuilder {
         pretty_env_logger::init();
         log::info!("Waking up with variables {:?}...", &env);
 
-        let storage: std::sync::Arc<ErasedStorage<State>> = SqliteStorage::open(&env.db_path, Json)
+        let storage: Arc<ErasedStorage<State>> = SqliteStorage::open(&env.db_path, Json)
             .await
             .map_err(|error| ErrorHandler {
                 msg: error.to_string(),
@@ -71,16 +93,78 @@ impl TelegrammBuilder {
             })?
             .erase();
 
+        let source: Arc<dyn CocktailSource> = match &env.dataset_path {
+            Some(path) => Arc::new(LocalSource::from_file(path)?),
+            None => Arc::new(HttpSource::from_env(&env)),
+        };
+        let pool = crate::storage::connect(&env.db_path).await?;
+        let mut cached = CachedSource::new(source, env.cache_size);
+        if env.persist_cache {
+            cached = cached.with_persistence(pool.clone()).await?;
+        }
+        let cache = Arc::new(cached);
+        let service = Arc::new(DrinksService::new(cache.clone()));
+
+        let profiles = Arc::new(ProfileStore::new(pool.clone()).await?);
+        let imported = profiles.import_from_dialogues().await?;
+        if imported > 0 {
+            log::info!("Imported {} profiles from saved dialogues.", imported);
+        }
+        let votes = Arc::new(VoteStore::new(pool.clone()).await?);
+        let history = Arc::new(HistoryStore::new(pool.clone()).await?);
+        let subscriptions = Arc::new(SubscriptionStore::new(pool.clone()).await?);
+        let stats = Arc::new(StatsStore::new(pool.clone()).await?);
+        let bans = Arc::new(BanStore::new(pool.clone()).await?);
+        let feedback = Arc::new(FeedbackStore::new(pool).await?);
+        let suggestions = feedback.import_file(SUGGESTIONS_PATH).await?;
+        if suggestions > 0 {
+            log::info!(
+                "Imported {} suggestions from {}.",
+                suggestions,
+                SUGGESTIONS_PATH
+            );
+        }
+        let admins = Arc::new(Admins(env.admin_ids.clone()));
+
         let handler = Self::create_handler();
 
         let bot = Bot::new(&env.bot_id).auto_send();
+        tokio::spawn(VoteHandler::watch(
+            bot.clone(),
+            service.clone(),
+            profiles.clone(),
+            votes.clone(),
+        ));
+        tokio::spawn(DailyHandler::watch(
+            bot.clone(),
+            service.clone(),
+            profiles.clone(),
+            subscriptions.clone(),
+            history.clone(),
+        ));
+        tokio::spawn(StatsStore::watch(stats.clone()));
 
         Dispatcher::builder(bot, handler)
-            .dependencies(dptree::deps![storage])
+            .dependencies(dptree::deps![
+                storage,
+                service,
+                profiles,
+                votes,
+                history,
+                subscriptions,
+                stats.clone(),
+                bans,
+                feedback,
+                admins,
+                cache
+            ])
+            // Banned users and group messages which are not for the bot end up here.
+            .default_handler(|_| async {})
             .enable_ctrlc_handler()
             .build()
             .dispatch()
             .await;
+        stats.flush().await?;
 
         Ok(())
     }
@@ -88,35 +172,55 @@ impl TelegrammBuilder {
         let commands_handler = teloxide::filter_command::<StartCommands, _>()
             .branch(dptree::entry().endpoint(CommandsHandler::handle_commands));
 
+        let admin_handler = teloxide::filter_command::<AdminCommands, _>()
+            .chain(dptree::filter(|message: Message, admins: Arc<Admins>| {
+                admins.is_admin(&message)
+            }))
+            .endpoint(AdminHandler::handle_commands);
+
         let message_handler = Update::filter_message()
+            .chain(dptree::filter_map(GroupHandler::addressed))
+            .chain(dptree::filter(AdminHandler::count))
+            .branch(admin_handler)
             .branch(commands_handler)
-            .branch(case![State::FindByName(setting)].endpoint(MessageHandler::find_by_name))
+            .branch(case![State::FindByName].endpoint(MessageHandler::find_by_name))
+            .branch(case![State::FindIngrByName].endpoint(MessageHandler::find_ingredient_by_name))
             .branch(
-                case![State::FindIngrByName(setting)]
-                    .endpoint(MessageHandler::find_ingredient_by_name),
+                case![State::WithIngredient(selected)].endpoint(MessageHandler::with_ingredient),
             )
-            .branch(case![State::WithIngredient(setting)].endpoint(MessageHandler::with_ingredient))
-            .branch(case![State::WithCategory(setting)].endpoint(MessageHandler::with_category))
-            .branch(
-                case![State::SettingsUpdate(settings, params)].endpoint(MessageHandler::settings),
-            )
-            .branch(case![State::Suggestion(settings)].endpoint(MessageHandler::suggestion))
+            .branch(case![State::WithCategory].endpoint(MessageHandler::with_category))
+            .branch(case![State::SettingsUpdate(params)].endpoint(MessageHandler::settings))
+            .branch(case![State::Suggestion].endpoint(MessageHandler::suggestion))
+            .branch(case![State::Servings(id)].endpoint(ScaleHandler::servings))
+            .branch(case![State::Batch(id)].endpoint(ScaleHandler::batch))
             .branch(dptree::entry().endpoint(MessageHandler::unexpected_message));
 
         let callback_handler = Update::filter_callback_query()
-            .branch(case![State::CallBack(settings)].endpoint(CallBackHandler::main_commands))
+            .chain(dptree::filter(AdminHandler::count))
             .branch(
-                case![State::CocktailForYou {
-                    all,
-                    game,
-                    settings
-                }]
-                .endpoint(CallBackHandler::game),
+                dptree::filter_map(|callback: CallbackQuery| {
+                    callback
+                        .data
+                        .and_then(|data| CallbackData::try_from(data.as_str()).ok())
+                })
+                .endpoint(CallBackHandler::inline_action),
+            )
+            .branch(case![State::CallBack].endpoint(CallBackHandler::main_commands))
+            .branch(case![State::Paging(pages)].endpoint(CallBackHandler::main_commands))
+            .branch(case![State::Settings].endpoint(CallBackHandler::callback_settings));
+
+        let inline_query_handler = Update::filter_inline_query()
+            .chain(dptree::filter(AdminHandler::count))
+            .endpoint(InlineQueryHandler::inline_query);
+
+        dptree::entry()
+            .chain(dptree::filter(AdminHandler::allow))
+            .chain(dptree::filter_async(GroupHandler::introduce))
+            .branch(inline_query_handler)
+            .branch(
+                dialogue::enter::<Update, ErasedStorage<State>, State, _>()
+                    .branch(message_handler)
+                    .branch(callback_handler),
             )
-            .branch(case![State::Settings(settings)].endpoint(CallBackHandler::callback_settings));
-
-        dialogue::enter::<Update, ErasedStorage<State>, State, _>()
-            .branch(message_handler)
-            .branch(callback_handler)
     }
 }
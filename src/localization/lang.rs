@@ -1,23 +1,128 @@
 use serde::{Deserialize, Serialize};
+use std::fmt::{Debug, Formatter};
 use std::ops::Deref;
+use std::sync::Arc;
 
 use crate::localization::schemas::LangConfig;
-use crate::localization::{ENG_CONFIG, UKR_CONFIG};
+use crate::localization::{CONFIGS, DEFAULT};
 
-/// todo: use this in lazy static to show path to localization
-#[derive(Debug, Serialize, Deserialize, Clone)]
-pub enum Lang {
-    Ukr,
-    Eng,
+/// Locale code of a file in `./localization`, like `en` for `en.json`,
+/// with the config loaded for it. A reload reaches the languages created after it.
+#[derive(Serialize, Deserialize, Clone)]
+#[serde(from = "String", into = "String")]
+pub struct Lang {
+    code: String,
+    config: Arc<LangConfig>,
 }
 
 impl Deref for Lang {
     type Target = LangConfig;
 
-    fn deref(&self) -> &'static Self::Target {
-        match self {
-            Lang::Eng => &ENG_CONFIG,
-            Lang::Ukr => &UKR_CONFIG,
+    fn deref(&self) -> &Self::Target {
+        &self.config
+    }
+}
+
+impl PartialEq for Lang {
+    fn eq(&self, other: &Self) -> bool {
+        self.code == other.code
+    }
+}
+
+impl Eq for Lang {}
+
+impl Debug for Lang {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        f.debug_tuple("Lang").field(&self.code).finish()
+    }
+}
+
+impl Default for Lang {
+    fn default() -> Self {
+        Lang::loaded(DEFAULT.to_string())
+    }
+}
+
+/// Profiles saved before the locale codes keep the enum names.
+impl From<String> for Lang {
+    fn from(code: String) -> Self {
+        match code.as_str() {
+            "Eng" => Lang::default(),
+            "Ukr" => Lang::loaded("uk".to_string()),
+            _ => Lang::loaded(code),
         }
     }
 }
+
+impl From<Lang> for String {
+    fn from(lang: Lang) -> Self {
+        lang.code
+    }
+}
+
+impl Lang {
+    pub fn new(code: &str) -> Lang {
+        Lang::from(code.to_string())
+    }
+
+    /// Codes without a file get English.
+    fn loaded(code: String) -> Lang {
+        let configs = CONFIGS.read().expect("Localization lock is poisoned.");
+        let config = configs
+            .get(&code)
+            .or_else(|| configs.get(DEFAULT))
+            .cloned()
+            .expect("The default localization is missing.");
+        Lang { code, config }
+    }
+
+    pub fn code(&self) -> &str {
+        &self.code
+    }
+
+    /// Loaded languages, English first.
+    pub fn available() -> Vec<Lang> {
+        let configs = CONFIGS.read().expect("Localization lock is poisoned.");
+        let mut codes = configs.keys().cloned().collect::<Vec<String>>();
+        codes.sort_by_key(|code| (code != DEFAULT, code.clone()));
+        drop(configs);
+        codes.into_iter().map(Lang::loaded).collect()
+    }
+
+    /// Language by the code Telegram sends with the user, like `uk` or `pt-br`,
+    /// English when there is no such localization.
+    pub fn from_code(code: Option<&str>) -> Lang {
+        let code = code.unwrap_or(DEFAULT).to_lowercase();
+        let language = code.split(['-', '_']).next().unwrap_or_default();
+        Lang::available()
+            .into_iter()
+            .find(|lang| lang.code == code)
+            .or_else(|| {
+                Lang::available()
+                    .into_iter()
+                    .find(|lang| lang.code == language)
+            })
+            .unwrap_or_default()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::localization::lang::Lang;
+
+    #[test]
+    fn test_from_code() {
+        assert_eq!(Lang::from_code(Some("uk")), Lang::new("uk"));
+        assert_eq!(Lang::from_code(Some("en-GB")), Lang::default());
+        assert_eq!(Lang::from_code(Some("xx")), Lang::default());
+        assert_eq!(Lang::from_code(None), Lang::default());
+    }
+
+    #[test]
+    fn test_legacy_names() {
+        let lang = serde_json::from_str::<Lang>("\"Ukr\"").unwrap();
+        assert_eq!(lang.code(), "uk");
+        assert_eq!(serde_json::to_string(&lang).unwrap(), "\"uk\"");
+        assert_eq!(Lang::new("de").send_commands, Lang::default().send_commands);
+    }
+}
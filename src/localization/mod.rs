@@ -1,33 +1,120 @@
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, RwLock};
+
 use lazy_static::lazy_static;
+use serde_json::Value;
 
+use crate::error::error_handler::{ErrorHandler, ErrorType};
 use crate::localization::schemas::LangConfig;
-use std::fs::File;
-use std::io::{BufReader, Read};
 
 pub mod lang;
 pub mod schemas;
 
+const PATH: &str = "./localization";
+/// Optional `<code>.json` files with drink instructions by drink id,
+/// for the languages the cocktails source does not have.
+const INSTRUCTIONS_PATH: &str = "./localization/instructions";
+/// Every other language falls back to it for missing keys.
+pub const DEFAULT: &str = "en";
+
 lazy_static! {
-    pub static ref ENG_CONFIG: LangConfig = {
-        let file = File::open("./localization/en.json").expect("could not open file");
-        let buffered_reader = BufReader::new(file);
-        let bytes = buffered_reader
-            .bytes()
-            .filter_map(|b| b.ok())
-            .collect::<Vec<u8>>();
-        serde_json::from_slice::<LangConfig>(bytes.as_slice())
-            .expect("Fail to parse the localization file.")
-    };
-    pub static ref UKR_CONFIG: LangConfig = {
-        let file = File::open("./localization/ukr.json").expect("could not open file");
-        let buffered_reader = BufReader::new(file);
-        let bytes = buffered_reader
-            .bytes()
-            .filter_map(|b| b.ok())
-            .collect::<Vec<u8>>();
-        serde_json::from_slice::<LangConfig>(bytes.as_slice())
-            .expect("Fail to parse the localization file.")
-    };
+    /// Configs by locale code, one per `<code>.json` in the localization directory.
+    pub static ref CONFIGS: RwLock<HashMap<String, Arc<LangConfig>>> =
+        RwLock::new(load_all(PATH).expect("Fail to load the localization files."));
+    static ref INSTRUCTIONS: RwLock<HashMap<String, HashMap<String, String>>> = RwLock::new(
+        load_instructions(INSTRUCTIONS_PATH).expect("Fail to load the instructions files.")
+    );
+}
+
+/// Rereads the localization directory, nothing changes if one of the files is broken.
+/// Old configs are dropped with the last `Lang` holding them.
+pub fn reload() -> Result<(), ErrorHandler> {
+    let configs = load_all(PATH)?;
+    let instructions = load_instructions(INSTRUCTIONS_PATH)?;
+    *CONFIGS.write().expect("Localization lock is poisoned.") = configs;
+    *INSTRUCTIONS
+        .write()
+        .expect("Localization lock is poisoned.") = instructions;
+    Ok(())
+}
+
+/// Instructions of the drink from the offline translation table.
+pub fn offline_instructions(code: &str, id: &str) -> Option<String> {
+    INSTRUCTIONS
+        .read()
+        .expect("Localization lock is poisoned.")
+        .get(code)
+        .and_then(|drinks| drinks.get(id))
+        .cloned()
+}
+
+fn load_all(dir: &str) -> Result<HashMap<String, Arc<LangConfig>>, ErrorHandler> {
+    let default = read(&Path::new(dir).join(format!("{}.json", DEFAULT)))?;
+    let mut configs = HashMap::new();
+    for (code, path) in json_files(dir)? {
+        let value = match code.as_str() {
+            DEFAULT => default.clone(),
+            _ => merge(default.clone(), read(&path)?),
+        };
+        let config = serde_json::from_value::<LangConfig>(value)?;
+        configs.insert(code, Arc::new(config));
+    }
+    Ok(configs)
+}
+
+/// The directory is optional, there are no offline translations without it.
+fn load_instructions(dir: &str) -> Result<HashMap<String, HashMap<String, String>>, ErrorHandler> {
+    if !Path::new(dir).is_dir() {
+        return Ok(HashMap::new());
+    }
+    let mut tables = HashMap::new();
+    for (code, path) in json_files(dir)? {
+        tables.insert(code, serde_json::from_value(read(&path)?)?);
+    }
+    Ok(tables)
+}
+
+/// `<code>.json` files of the directory by their code.
+fn json_files(dir: &str) -> Result<Vec<(String, PathBuf)>, ErrorHandler> {
+    let entries = std::fs::read_dir(dir).map_err(|err| ErrorHandler {
+        msg: format!("Fail to read {}: {}", dir, err),
+        ty: ErrorType::File,
+    })?;
+    Ok(entries
+        .flatten()
+        .map(|entry| entry.path())
+        .filter(|path| path.extension().is_some_and(|ext| ext == "json"))
+        .filter_map(|path| {
+            let code = path.file_stem()?.to_str()?.to_string();
+            Some((code, path))
+        })
+        .collect())
+}
+
+/// Keys of `value` replace the ones of `base`, nested objects are merged.
+fn merge(base: Value, value: Value) -> Value {
+    match (base, value) {
+        (Value::Object(mut base), Value::Object(value)) => {
+            for (key, value) in value {
+                let merged = match base.remove(&key) {
+                    Some(old) => merge(old, value),
+                    None => value,
+                };
+                base.insert(key, merged);
+            }
+            Value::Object(base)
+        }
+        (_, value) => value,
+    }
+}
+
+fn read(path: &Path) -> Result<Value, ErrorHandler> {
+    let bytes = std::fs::read(path).map_err(|err| ErrorHandler {
+        msg: format!("Fail to read {}: {}", path.display(), err),
+        ty: ErrorType::File,
+    })?;
+    Ok(serde_json::from_slice::<Value>(&bytes)?)
 }
 
 #[cfg(test)]
@@ -35,25 +122,26 @@ mod test {
     use std::fs::File;
     use std::io::{BufReader, Read};
 
+    use serde_json::json;
+
     use crate::localization::lang::Lang;
-    use crate::localization::{ENG_CONFIG, UKR_CONFIG};
+    use crate::localization::{merge, CONFIGS};
 
     use crate::localization::schemas::LangConfig;
 
     #[test]
     pub fn test() {
-        assert_eq!(&*Lang::Ukr.send_commands, UKR_CONFIG.send_commands)
+        assert_eq!(
+            &*Lang::new("uk").send_commands,
+            CONFIGS.read().unwrap()["uk"].send_commands
+        )
     }
 
     #[test]
     fn test2() {
-        let file = File::open("./localization/ukr.json").expect("could not open file");
-        let mut buffered_reader = BufReader::new(file);
-        let bytes = buffered_reader
-            .bytes()
-            .filter(|b| b.is_ok())
-            .map(|b| b.unwrap())
-            .collect::<Vec<u8>>();
+        let file = File::open("./localization/uk.json").expect("could not open file");
+        let buffered_reader = BufReader::new(file);
+        let bytes = buffered_reader.bytes().flatten().collect::<Vec<u8>>();
         let result = serde_json::from_slice::<LangConfig>(bytes.as_slice());
         assert!(result.is_ok());
         println!("{:?}", result.unwrap());
@@ -61,10 +149,41 @@ mod test {
 
     #[test]
     fn test3() {
-        assert_eq!(ENG_CONFIG.send_commands, "Here we go: ");
+        assert_eq!(CONFIGS.read().unwrap()["en"].send_commands, "Here we go: ");
     }
     #[test]
     fn test4() {
-        assert_eq!(UKR_CONFIG.send_commands, "Поїхали!: ");
+        assert_eq!(CONFIGS.read().unwrap()["uk"].send_commands, "Поїхали!: ");
+    }
+
+    #[test]
+    fn test_reload() {
+        let before = Lang::new("uk");
+        crate::localization::reload().unwrap();
+        assert_eq!(Lang::default().send_commands, "Here we go: ");
+        assert_eq!(before.send_commands, Lang::new("uk").send_commands);
+    }
+
+    #[test]
+    fn test_merge() {
+        let base = json!({"a": "en", "b": {"c": "en", "d": "en"}});
+        let value = json!({"b": {"c": "uk"}});
+        assert_eq!(
+            merge(base, value),
+            json!({"a": "en", "b": {"c": "uk", "d": "en"}})
+        );
+    }
+
+    #[test]
+    fn test_names() {
+        let names = &Lang::new("uk").names;
+        assert_eq!(names.ingredient("vodka"), "Горілка");
+        assert_eq!(names.ingredient("Unobtainium"), "Unobtainium");
+        assert_eq!(names.ingredient_key(" горілка "), "Vodka");
+        assert_eq!(names.category_key("Ordinary Drink"), "Ordinary Drink");
+        assert_eq!(
+            Lang::default().names.glass("Highball glass"),
+            "Highball glass"
+        );
     }
 }
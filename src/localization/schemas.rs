@@ -4,41 +4,81 @@ use serde::Deserialize;
 
 #[derive(Debug, Deserialize)]
 pub struct LangConfig {
+    /// Name of the language in the language picker.
+    pub name: String,
+    /// Locale codes to take the drink instructions from when there are none in this
+    /// language, English is the last resort anyway.
+    pub instructions_fallback: Vec<String>,
     pub send_commands: String,
     pub fail_messages: FailMessages,
     pub todo: Todo,
     pub service_responses: ServiceResponses,
     pub settings_descriptions: SettingsDescriptions,
     pub buttons: MainButtons,
+    pub bar: BarMessages,
+    pub favorites: FavoritesMessages,
+    pub vote: VoteMessages,
+    pub game: GameMessages,
+    pub recommend: RecommendMessages,
+    pub daily: DailyMessages,
+    pub scale: ScaleMessages,
+    pub shopping: ShoppingMessages,
+    pub admin: AdminMessages,
+    pub names: Names,
 }
 
 #[derive(Debug, Deserialize)]
 pub struct MainButtons {
     pub main: HashMap<String, String>,
     pub settings: HashMap<String, String>,
+    pub inline: InlineButtons,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct InlineButtons {
+    pub details: String,
+    pub search: String,
+    pub bar_add: String,
+    pub save: String,
+    pub remove: String,
+    pub prev: String,
+    pub next: String,
+    pub page: String,
+    pub of: String,
+    pub not_for_me: String,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct BarMessages {
+    pub list: String,
+    pub empty: String,
+    pub added: String,
+    pub removed: String,
+    pub nothing: String,
+    pub need_names: String,
 }
 
 #[derive(Debug, Deserialize)]
 pub struct FailMessages {
     pub unexpected: String,
-    pub game_limit: String,
     pub need_number: String,
     pub non_results: String,
     pub wrong_category: String,
     pub wrong_ingredient: String,
-    pub try_again: String,
     pub suggestion: String,
+    pub expired: String,
+    pub admins_only: String,
 }
 
 #[derive(Debug, Deserialize)]
 pub struct Todo {
     pub settings_set_limit: String,
     pub settings_set_name: String,
-    pub game_choose: String,
     pub category_write: String,
     pub ingredient_write: String,
     pub find_cocktails_write: String,
     pub find_ingredient_write: String,
+    pub ingredients_pick: String,
     pub suggestion: String,
 }
 
@@ -52,17 +92,192 @@ pub struct ServiceResponses {
     pub glass: String,
     pub ingredient_name: String,
     pub description: String,
+    pub uses: String,
+    pub missing: String,
 }
 
 #[derive(Debug, Deserialize)]
 pub struct SettingsDescriptions {
     pub name: String,
     pub lang: String,
-    pub lang_ukr: String,
-    pub lang_eng: String,
     pub yes: String,
     pub no: String,
     pub image: String,
     pub limit: String,
     pub limit_name: String,
+    pub units: String,
+    pub units_original: String,
+    pub units_metric: String,
+    pub units_imperial: String,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct FavoritesMessages {
+    pub saved: String,
+    pub already: String,
+    pub removed: String,
+    pub empty: String,
+    pub title: String,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct VoteMessages {
+    pub title: String,
+    pub closed: String,
+    pub winner: String,
+    pub nobody: String,
+    pub counted: String,
+    pub over: String,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct GameMessages {
+    pub alcohol: String,
+    pub category: String,
+    pub glass: String,
+    pub ingredient: String,
+    pub strength: String,
+    pub sweetness: String,
+    pub alco: String,
+    pub non_alco: String,
+    pub strong: String,
+    pub light: String,
+    pub sweet: String,
+    pub dry: String,
+    pub either: String,
+    pub skip: String,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct RecommendMessages {
+    pub empty: String,
+    pub nothing: String,
+    pub noted: String,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct DailyMessages {
+    pub title: String,
+    pub subscribed: String,
+    pub unsubscribed: String,
+    pub not_subscribed: String,
+    pub format: String,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct ScaleMessages {
+    pub button: String,
+    pub title: String,
+    pub custom: String,
+    pub batch: String,
+    pub ask_servings: String,
+    pub ask_guests: String,
+    pub servings: String,
+    pub batch_title: String,
+    pub guests: String,
+    pub dilution: String,
+    pub total: String,
+    pub bottles: String,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct ShoppingMessages {
+    pub add: String,
+    pub added: String,
+    pub already: String,
+    pub favorites: String,
+    pub empty: String,
+    pub selected: String,
+    pub message: String,
+    pub file: String,
+    pub clear: String,
+    pub cleared: String,
+    pub title: String,
+    pub nothing: String,
+    pub in_bar: String,
+    pub spirit: String,
+    pub liqueur: String,
+    pub mixer: String,
+    pub other: String,
+    pub csv_header: String,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct AdminMessages {
+    pub chats: String,
+    pub profiles: String,
+    pub active: String,
+    pub requests: String,
+    pub persisted: String,
+    pub need_text: String,
+    pub broadcast_started: String,
+    pub broadcast_done: String,
+    pub no_suggestions: String,
+    pub reloaded: String,
+    pub reload_failed: String,
+    pub need_id: String,
+    pub banned: String,
+    pub already_banned: String,
+    pub unbanned: String,
+    pub not_banned: String,
+    pub feedback_not_found: String,
+    pub need_reply: String,
+    pub replied: String,
+    pub author_unknown: String,
+    pub reply_title: String,
+}
+
+/// Translations by the English name from the cocktails source.
+#[derive(Debug, Deserialize)]
+pub struct Names {
+    pub ingredients: HashMap<String, String>,
+    pub categories: HashMap<String, String>,
+    pub glasses: HashMap<String, String>,
+}
+
+impl Names {
+    pub fn ingredient(&self, name: &str) -> String {
+        translate(&self.ingredients, name).unwrap_or_else(|| name.to_string())
+    }
+
+    pub fn category(&self, name: &str) -> String {
+        translate(&self.categories, name).unwrap_or_else(|| name.to_string())
+    }
+
+    pub fn glass(&self, name: &str) -> String {
+        translate(&self.glasses, name).unwrap_or_else(|| name.to_string())
+    }
+
+    /// For lists which do not tell what they are made of.
+    pub fn any(&self, name: &str) -> String {
+        translate(&self.ingredients, name)
+            .or_else(|| translate(&self.categories, name))
+            .or_else(|| translate(&self.glasses, name))
+            .unwrap_or_else(|| name.to_string())
+    }
+
+    /// English name of the ingredient the user typed, the input itself when it is unknown.
+    pub fn ingredient_key(&self, input: &str) -> String {
+        key(&self.ingredients, input)
+    }
+
+    pub fn category_key(&self, input: &str) -> String {
+        key(&self.categories, input)
+    }
+}
+
+fn translate(names: &HashMap<String, String>, name: &str) -> Option<String> {
+    names
+        .iter()
+        .find(|(key, _)| key.eq_ignore_ascii_case(name.trim()))
+        .map(|(_, value)| value.clone())
+}
+
+fn key(names: &HashMap<String, String>, input: &str) -> String {
+    let input = input.trim();
+    names
+        .iter()
+        .find(|(_, value)| value.to_lowercase() == input.to_lowercase())
+        .map(|(key, _)| key.clone())
+        .unwrap_or_else(|| input.to_string())
 }
@@ -0,0 +1,91 @@
+use std::collections::HashSet;
+use std::sync::RwLock;
+
+use sqlx::sqlite::SqlitePool;
+use sqlx::Row;
+
+use crate::error::error_handler::ErrorHandler;
+
+/// Banned users, also kept in memory since every update is checked.
+pub struct BanStore {
+    pool: SqlitePool,
+    banned: RwLock<HashSet<i64>>,
+}
+
+impl BanStore {
+    pub async fn new(pool: SqlitePool) -> Result<Self, ErrorHandler> {
+        sqlx::query(
+            "CREATE TABLE IF NOT EXISTS bans (
+                user_id INTEGER PRIMARY KEY,
+                banned_at INTEGER NOT NULL
+            )",
+        )
+        .execute(&pool)
+        .await?;
+        let banned = sqlx::query("SELECT user_id FROM bans")
+            .fetch_all(&pool)
+            .await?
+            .iter()
+            .map(|row| row.try_get("user_id"))
+            .collect::<Result<HashSet<i64>, sqlx::Error>>()?;
+        Ok(Self {
+            pool,
+            banned: RwLock::new(banned),
+        })
+    }
+
+    pub fn is_banned(&self, user_id: i64) -> bool {
+        self.banned
+            .read()
+            .expect("Ban lock is poisoned.")
+            .contains(&user_id)
+    }
+
+    /// `false` when the user was already banned.
+    pub async fn ban(&self, user_id: i64) -> Result<bool, ErrorHandler> {
+        sqlx::query("INSERT OR IGNORE INTO bans (user_id, banned_at) VALUES (?, ?)")
+            .bind(user_id)
+            .bind(super::now())
+            .execute(&self.pool)
+            .await?;
+        Ok(self
+            .banned
+            .write()
+            .expect("Ban lock is poisoned.")
+            .insert(user_id))
+    }
+
+    /// `false` when the user was not banned.
+    pub async fn unban(&self, user_id: i64) -> Result<bool, ErrorHandler> {
+        sqlx::query("DELETE FROM bans WHERE user_id = ?")
+            .bind(user_id)
+            .execute(&self.pool)
+            .await?;
+        Ok(self
+            .banned
+            .write()
+            .expect("Ban lock is poisoned.")
+            .remove(&user_id))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::storage::bans::BanStore;
+    use crate::utils::testing::test_pool;
+
+    #[tokio::test]
+    async fn test_ban() {
+        let pool = test_pool().await;
+        let bans = BanStore::new(pool.clone()).await.unwrap();
+        assert!(bans.ban(7).await.unwrap());
+        assert!(!bans.ban(7).await.unwrap());
+        assert!(bans.is_banned(7));
+
+        let reloaded = BanStore::new(pool).await.unwrap();
+        assert!(reloaded.is_banned(7));
+        assert!(reloaded.unban(7).await.unwrap());
+        assert!(!reloaded.is_banned(7));
+        assert!(!reloaded.unban(7).await.unwrap());
+    }
+}
@@ -0,0 +1,269 @@
+use sqlx::sqlite::{SqlitePool, SqliteRow};
+use sqlx::Row;
+
+use crate::error::error_handler::{ErrorHandler, ErrorType};
+
+/// Suggestions were appended to this file before the feedback table existed.
+pub const SUGGESTIONS_PATH: &str = "./suggestion_bags/suggestion.txt";
+const SUGGESTIONS_SEPARATOR: &str = "_____________________________";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FeedbackStatus {
+    New,
+    Acknowledged,
+    Resolved,
+}
+
+impl FeedbackStatus {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            FeedbackStatus::New => "new",
+            FeedbackStatus::Acknowledged => "acknowledged",
+            FeedbackStatus::Resolved => "resolved",
+        }
+    }
+}
+
+impl TryFrom<&str> for FeedbackStatus {
+    type Error = ErrorHandler;
+
+    fn try_from(value: &str) -> Result<Self, Self::Error> {
+        match value {
+            "new" => Ok(FeedbackStatus::New),
+            "acknowledged" | "ack" => Ok(FeedbackStatus::Acknowledged),
+            "resolved" => Ok(FeedbackStatus::Resolved),
+            _ => Err(ErrorHandler {
+                msg: format!("Unknown feedback status: {}", value),
+                ty: ErrorType::Parse,
+            }),
+        }
+    }
+}
+
+/// A suggestion or a bug report sent with `/suggestion`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Feedback {
+    pub id: i64,
+    pub user_id: i64,
+    pub username: Option<String>,
+    /// The chat to reply to.
+    pub chat_id: i64,
+    pub lang: String,
+    pub text: String,
+    /// Telegram file id of the attached screenshot.
+    pub photo: Option<String>,
+    pub status: FeedbackStatus,
+    pub created_at: i64,
+}
+
+pub struct FeedbackStore {
+    pool: SqlitePool,
+}
+
+impl FeedbackStore {
+    pub async fn new(pool: SqlitePool) -> Result<Self, ErrorHandler> {
+        sqlx::query(
+            "CREATE TABLE IF NOT EXISTS feedback (
+                id INTEGER PRIMARY KEY AUTOINCREMENT,
+                user_id INTEGER NOT NULL,
+                username TEXT,
+                chat_id INTEGER NOT NULL,
+                lang TEXT NOT NULL,
+                text TEXT NOT NULL,
+                photo TEXT,
+                status TEXT NOT NULL,
+                created_at INTEGER NOT NULL
+            )",
+        )
+        .execute(&pool)
+        .await?;
+        Ok(Self { pool })
+    }
+
+    /// `id`, `status` and `created_at` of the feedback are ignored. Returns the new id.
+    pub async fn add(&self, feedback: &Feedback) -> Result<i64, ErrorHandler> {
+        let result = sqlx::query(
+            "INSERT INTO feedback (user_id, username, chat_id, lang, text, photo, status, created_at)
+            VALUES (?, ?, ?, ?, ?, ?, ?, ?)",
+        )
+        .bind(feedback.user_id)
+        .bind(&feedback.username)
+        .bind(feedback.chat_id)
+        .bind(&feedback.lang)
+        .bind(&feedback.text)
+        .bind(&feedback.photo)
+        .bind(FeedbackStatus::New.as_str())
+        .bind(super::now())
+        .execute(&self.pool)
+        .await?;
+        Ok(result.last_insert_rowid())
+    }
+
+    pub async fn get(&self, id: i64) -> Result<Option<Feedback>, ErrorHandler> {
+        sqlx::query("SELECT * FROM feedback WHERE id = ?")
+            .bind(id)
+            .fetch_optional(&self.pool)
+            .await?
+            .map(|row| Self::from_row(&row))
+            .transpose()
+    }
+
+    /// The latest first, any status when `status` is `None`.
+    pub async fn list(
+        &self,
+        status: Option<FeedbackStatus>,
+        limit: usize,
+    ) -> Result<Vec<Feedback>, ErrorHandler> {
+        let rows = match status {
+            Some(status) => {
+                sqlx::query("SELECT * FROM feedback WHERE status = ? ORDER BY id DESC LIMIT ?")
+                    .bind(status.as_str())
+                    .bind(limit as i64)
+                    .fetch_all(&self.pool)
+                    .await?
+            }
+            None => {
+                sqlx::query("SELECT * FROM feedback ORDER BY id DESC LIMIT ?")
+                    .bind(limit as i64)
+                    .fetch_all(&self.pool)
+                    .await?
+            }
+        };
+        rows.iter().map(Self::from_row).collect()
+    }
+
+    /// Moves the suggestions of the old file into the table and renames the file,
+    /// so they are imported once. They have no author to reply to.
+    pub async fn import_file(&self, path: &str) -> Result<usize, ErrorHandler> {
+        let file_error = |err: std::io::Error| ErrorHandler {
+            msg: format!("Fail to import suggestions from {}: {}", path, err),
+            ty: ErrorType::File,
+        };
+        let text = match std::fs::read_to_string(path) {
+            Ok(text) => text,
+            Err(err) if err.kind() == std::io::ErrorKind::NotFound => return Ok(0),
+            Err(err) => return Err(file_error(err)),
+        };
+        let suggestions = Self::split_suggestions(&text);
+        let mut transaction = self.pool.begin().await?;
+        for text in suggestions.iter() {
+            sqlx::query(
+                "INSERT INTO feedback (user_id, username, chat_id, lang, text, photo, status, created_at)
+                VALUES (0, NULL, 0, 'en', ?, NULL, ?, ?)",
+            )
+            .bind(text)
+            .bind(FeedbackStatus::New.as_str())
+            .bind(super::now())
+            .execute(&mut transaction)
+            .await?;
+        }
+        transaction.commit().await?;
+        std::fs::rename(path, format!("{}.imported", path)).map_err(file_error)?;
+        Ok(suggestions.len())
+    }
+
+    fn split_suggestions(text: &str) -> Vec<String> {
+        text.lines()
+            .collect::<Vec<&str>>()
+            .split(|line| line.starts_with(SUGGESTIONS_SEPARATOR))
+            .map(|lines| lines.join("\n").trim().to_string())
+            .filter(|suggestion| !suggestion.is_empty())
+            .collect()
+    }
+
+    /// `false` when there is no such feedback.
+    pub async fn set_status(&self, id: i64, status: FeedbackStatus) -> Result<bool, ErrorHandler> {
+        let result = sqlx::query("UPDATE feedback SET status = ? WHERE id = ?")
+            .bind(status.as_str())
+            .bind(id)
+            .execute(&self.pool)
+            .await?;
+        Ok(result.rows_affected() > 0)
+    }
+
+    fn from_row(row: &SqliteRow) -> Result<Feedback, ErrorHandler> {
+        Ok(Feedback {
+            id: row.try_get("id")?,
+            user_id: row.try_get("user_id")?,
+            username: row.try_get("username")?,
+            chat_id: row.try_get("chat_id")?,
+            lang: row.try_get("lang")?,
+            text: row.try_get("text")?,
+            photo: row.try_get("photo")?,
+            status: FeedbackStatus::try_from(row.try_get::<String, _>("status")?.as_str())?,
+            created_at: row.try_get("created_at")?,
+        })
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::storage::feedback::{Feedback, FeedbackStatus, FeedbackStore};
+    use crate::utils::testing::test_pool;
+
+    fn feedback(text: &str, photo: Option<&str>) -> Feedback {
+        Feedback {
+            id: 0,
+            user_id: 7,
+            username: Some("tester".to_string()),
+            chat_id: 7,
+            lang: "Eng".to_string(),
+            text: text.to_string(),
+            photo: photo.map(str::to_string),
+            status: FeedbackStatus::Resolved,
+            created_at: 0,
+        }
+    }
+
+    #[tokio::test]
+    async fn test_feedback() {
+        let pool = test_pool().await;
+        let store = FeedbackStore::new(pool).await.unwrap();
+        let first = store.add(&feedback("Typo", None)).await.unwrap();
+        let second = store
+            .add(&feedback("Crash", Some("photo-id")))
+            .await
+            .unwrap();
+
+        let saved = store.get(second).await.unwrap().unwrap();
+        assert_eq!(saved.status, FeedbackStatus::New);
+        assert_eq!(saved.photo.as_deref(), Some("photo-id"));
+
+        assert!(store
+            .set_status(first, FeedbackStatus::Acknowledged)
+            .await
+            .unwrap());
+        assert!(!store
+            .set_status(42, FeedbackStatus::Resolved)
+            .await
+            .unwrap());
+
+        let new = store.list(Some(FeedbackStatus::New), 10).await.unwrap();
+        assert_eq!(new.len(), 1);
+        assert_eq!(new[0].text, "Crash");
+        let all = store.list(None, 10).await.unwrap();
+        assert_eq!(
+            all.iter().map(|feedback| feedback.id).collect::<Vec<i64>>(),
+            vec![second, first]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_import_file() {
+        let store = FeedbackStore::new(test_pool().await).await.unwrap();
+        let path = std::env::temp_dir().join(format!("suggestion-{}.txt", std::process::id()));
+        let path = path.to_str().unwrap();
+        std::fs::write(
+            path,
+            "\nTypo\n_____________________________\n\nCrash\non start\n______________________________\n",
+        )
+        .unwrap();
+
+        assert_eq!(store.import_file(path).await.unwrap(), 2);
+        assert_eq!(store.import_file(path).await.unwrap(), 0);
+        let all = store.list(None, 10).await.unwrap();
+        assert_eq!(all[0].text, "Crash\non start");
+        assert_eq!(all[1].text, "Typo");
+        std::fs::remove_file(format!("{}.imported", path)).unwrap();
+    }
+}
@@ -0,0 +1,151 @@
+use sqlx::sqlite::SqlitePool;
+use sqlx::Row;
+use teloxide::types::ChatId;
+
+use crate::error::error_handler::{ErrorHandler, ErrorType};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Event {
+    Viewed,
+    Saved,
+    /// The game ended on the drink.
+    Played,
+    Disliked,
+}
+
+impl Event {
+    /// How much the event tells about the taste of the user.
+    pub fn weight(&self) -> i64 {
+        match self {
+            Event::Viewed => 1,
+            Event::Played => 2,
+            Event::Saved => 3,
+            Event::Disliked => -4,
+        }
+    }
+
+    fn as_str(&self) -> &'static str {
+        match self {
+            Event::Viewed => "viewed",
+            Event::Saved => "saved",
+            Event::Played => "played",
+            Event::Disliked => "disliked",
+        }
+    }
+}
+
+impl TryFrom<&str> for Event {
+    type Error = ErrorHandler;
+
+    fn try_from(value: &str) -> Result<Self, Self::Error> {
+        match value {
+            "viewed" => Ok(Event::Viewed),
+            "saved" => Ok(Event::Saved),
+            "played" => Ok(Event::Played),
+            "disliked" => Ok(Event::Disliked),
+            _ => Err(ErrorHandler {
+                msg: format!("Unknown history event: {}", value),
+                ty: ErrorType::Database,
+            }),
+        }
+    }
+}
+
+/// Drinks every user has seen, keyed by the user's private chat id.
+pub struct HistoryStore {
+    pool: SqlitePool,
+}
+
+impl HistoryStore {
+    pub async fn new(pool: SqlitePool) -> Result<Self, ErrorHandler> {
+        sqlx::query(
+            "CREATE TABLE IF NOT EXISTS history (
+                user_id INTEGER NOT NULL,
+                drink_id TEXT NOT NULL,
+                event TEXT NOT NULL,
+                created_at INTEGER NOT NULL,
+                PRIMARY KEY (user_id, drink_id, event)
+            )",
+        )
+        .execute(&pool)
+        .await?;
+        Ok(Self { pool })
+    }
+
+    /// The same event for the same drink is kept once.
+    pub async fn record(
+        &self,
+        user: ChatId,
+        drink_id: &str,
+        event: Event,
+    ) -> Result<(), ErrorHandler> {
+        sqlx::query(
+            "INSERT INTO history (user_id, drink_id, event, created_at) VALUES (?, ?, ?, ?)
+            ON CONFLICT(user_id, drink_id, event) DO UPDATE SET created_at = excluded.created_at",
+        )
+        .bind(user.0)
+        .bind(drink_id)
+        .bind(event.as_str())
+        .bind(super::now())
+        .execute(&self.pool)
+        .await?;
+        Ok(())
+    }
+
+    /// The latest first.
+    pub async fn events(&self, user: ChatId) -> Result<Vec<(String, Event)>, ErrorHandler> {
+        let rows = sqlx::query(
+            "SELECT drink_id, event FROM history WHERE user_id = ? ORDER BY created_at DESC",
+        )
+        .bind(user.0)
+        .fetch_all(&self.pool)
+        .await?;
+        rows.into_iter()
+            .map(|row| {
+                Ok((
+                    row.try_get("drink_id")?,
+                    Event::try_from(row.try_get::<String, _>("event")?.as_str())?,
+                ))
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use teloxide::types::ChatId;
+
+    use crate::storage::history::{Event, HistoryStore};
+    use crate::utils::testing::test_pool;
+
+    #[tokio::test]
+    async fn test_record() {
+        let history = HistoryStore::new(test_pool().await).await.unwrap();
+        history
+            .record(ChatId(1), "11000", Event::Viewed)
+            .await
+            .unwrap();
+        history
+            .record(ChatId(1), "11000", Event::Viewed)
+            .await
+            .unwrap();
+        history
+            .record(ChatId(1), "11001", Event::Disliked)
+            .await
+            .unwrap();
+        history
+            .record(ChatId(2), "11002", Event::Saved)
+            .await
+            .unwrap();
+
+        let mut events = history.events(ChatId(1)).await.unwrap();
+        events.sort_by(|first, second| first.0.cmp(&second.0));
+        assert_eq!(
+            events,
+            vec![
+                ("11000".to_string(), Event::Viewed),
+                ("11001".to_string(), Event::Disliked)
+            ]
+        );
+    }
+}
@@ -0,0 +1,23 @@
+pub mod bans;
+pub mod feedback;
+pub mod history;
+pub mod profiles;
+pub mod stats;
+pub mod subscriptions;
+pub mod votes;
+
+use sqlx::sqlite::SqlitePool;
+
+use crate::error::error_handler::ErrorHandler;
+
+/// Opens the same SQLite file as the dialogue storage.
+pub async fn connect(path: &str) -> Result<SqlitePool, ErrorHandler> {
+    Ok(SqlitePool::connect(&format!("sqlite:{}?mode=rwc", path)).await?)
+}
+
+pub fn now() -> i64 {
+    std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .map(|duration| duration.as_secs() as i64)
+        .unwrap_or_default()
+}
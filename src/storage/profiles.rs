@@ -0,0 +1,234 @@
+use std::collections::HashSet;
+use std::sync::{Mutex, MutexGuard};
+
+use serde_json::Value;
+use sqlx::sqlite::SqlitePool;
+use sqlx::Row;
+use teloxide::types::ChatId;
+
+use crate::error::error_handler::ErrorHandler;
+use crate::telegramm::settings::settings::UserSettings;
+use crate::telegramm::state::State;
+
+/// User preferences kept apart from the dialogue state, keyed by chat id.
+pub struct ProfileStore {
+    pool: SqlitePool,
+    /// Chats which are known to have a profile.
+    known: Mutex<HashSet<ChatId>>,
+}
+
+impl ProfileStore {
+    pub async fn new(pool: SqlitePool) -> Result<Self, ErrorHandler> {
+        sqlx::query(
+            "CREATE TABLE IF NOT EXISTS profiles (
+                chat_id INTEGER PRIMARY KEY,
+                settings TEXT NOT NULL,
+                updated_at INTEGER NOT NULL
+            )",
+        )
+        .execute(&pool)
+        .await?;
+        Ok(Self {
+            pool,
+            known: Mutex::new(HashSet::new()),
+        })
+    }
+
+    /// Chats without a profile get the default one.
+    pub async fn get(&self, chat_id: ChatId) -> Result<UserSettings, ErrorHandler> {
+        Ok(self.find(chat_id).await?.unwrap_or_default())
+    }
+
+    pub async fn find(&self, chat_id: ChatId) -> Result<Option<UserSettings>, ErrorHandler> {
+        let row = sqlx::query("SELECT settings FROM profiles WHERE chat_id = ?")
+            .bind(chat_id.0)
+            .fetch_optional(&self.pool)
+            .await?;
+        match row {
+            Some(row) => Ok(Some(serde_json::from_str(
+                &row.try_get::<String, _>("settings")?,
+            )?)),
+            None => Ok(None),
+        }
+    }
+
+    pub async fn save(&self, chat_id: ChatId, settings: &UserSettings) -> Result<(), ErrorHandler> {
+        sqlx::query(
+            "INSERT INTO profiles (chat_id, settings, updated_at) VALUES (?, ?, ?)
+            ON CONFLICT(chat_id) DO UPDATE
+            SET settings = excluded.settings, updated_at = excluded.updated_at",
+        )
+        .bind(chat_id.0)
+        .bind(serde_json::to_string(settings)?)
+        .bind(super::now())
+        .execute(&self.pool)
+        .await?;
+        Ok(())
+    }
+
+    /// Keeps the existing profile, returns whether the new one is saved.
+    pub async fn create(
+        &self,
+        chat_id: ChatId,
+        settings: &UserSettings,
+    ) -> Result<bool, ErrorHandler> {
+        let result = sqlx::query(
+            "INSERT OR IGNORE INTO profiles (chat_id, settings, updated_at) VALUES (?, ?, ?)",
+        )
+        .bind(chat_id.0)
+        .bind(serde_json::to_string(settings)?)
+        .bind(super::now())
+        .execute(&self.pool)
+        .await?;
+        Ok(result.rows_affected() > 0)
+    }
+
+    /// Same as `create`, but only the first call for a chat touches the database.
+    pub async fn create_once<F>(&self, chat_id: ChatId, settings: F) -> Result<bool, ErrorHandler>
+    where
+        F: FnOnce() -> UserSettings,
+    {
+        if self.known().contains(&chat_id) {
+            return Ok(false);
+        }
+        let created = self.create(chat_id, &settings()).await?;
+        self.known().insert(chat_id);
+        Ok(created)
+    }
+
+    fn known(&self) -> MutexGuard<'_, HashSet<ChatId>> {
+        self.known.lock().expect("Known chats lock is poisoned.")
+    }
+
+    /// Moves the settings out of dialogues saved before the profiles existed,
+    /// such dialogues start over. Returns the number of imported profiles.
+    pub async fn import_from_dialogues(&self) -> Result<usize, ErrorHandler> {
+        let table = sqlx::query(
+            "SELECT name FROM sqlite_master WHERE type = 'table' AND name = 'teloxide_dialogues'",
+        )
+        .fetch_optional(&self.pool)
+        .await?;
+        if table.is_none() {
+            return Ok(0);
+        }
+        let rows = sqlx::query("SELECT chat_id, dialogue FROM teloxide_dialogues")
+            .fetch_all(&self.pool)
+            .await?;
+        let mut imported = 0;
+        for row in rows {
+            let chat_id: i64 = row.try_get("chat_id")?;
+            let dialogue: Vec<u8> = row.try_get("dialogue")?;
+            let value = match serde_json::from_slice::<Value>(&dialogue) {
+                Ok(value) => value,
+                Err(_) => continue,
+            };
+            if serde_json::from_value::<State>(value.clone()).is_ok() {
+                continue;
+            }
+            if let Some(settings) = settings_from_dialogue(&value) {
+                let result = sqlx::query(
+                    "INSERT OR IGNORE INTO profiles (chat_id, settings, updated_at) VALUES (?, ?, ?)",
+                )
+                .bind(chat_id)
+                .bind(serde_json::to_string(&settings)?)
+                .bind(super::now())
+                .execute(&self.pool)
+                .await?;
+                imported += result.rows_affected() as usize;
+            }
+            sqlx::query("UPDATE teloxide_dialogues SET dialogue = ? WHERE chat_id = ?")
+                .bind(serde_json::to_vec(&State::Start)?)
+                .bind(chat_id)
+                .execute(&self.pool)
+                .await?;
+        }
+        Ok(imported)
+    }
+}
+
+fn settings_from_dialogue(value: &Value) -> Option<UserSettings> {
+    match value {
+        Value::Object(map) => serde_json::from_value::<UserSettings>(value.clone())
+            .ok()
+            .or_else(|| map.values().find_map(settings_from_dialogue)),
+        Value::Array(values) => values.iter().find_map(settings_from_dialogue),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use teloxide::types::ChatId;
+
+    use crate::localization::lang::Lang;
+    use crate::storage::profiles::ProfileStore;
+    use crate::telegramm::settings::settings::UserSettings;
+    use crate::utils::testing::test_pool;
+
+    #[tokio::test]
+    async fn test_create_once() {
+        let profiles = ProfileStore::new(test_pool().await).await.unwrap();
+        let settings = || UserSettings {
+            limit_of_messages: 3,
+            ..UserSettings::default()
+        };
+        assert!(profiles.create_once(ChatId(1), settings).await.unwrap());
+        assert!(!profiles.create_once(ChatId(1), settings).await.unwrap());
+        assert_eq!(profiles.get(ChatId(1)).await.unwrap().limit_of_messages, 3);
+    }
+
+    #[tokio::test]
+    async fn test_save_and_get() {
+        let profiles = ProfileStore::new(test_pool().await).await.unwrap();
+        assert_eq!(profiles.get(ChatId(1)).await.unwrap().limit_of_messages, 10);
+
+        let settings = UserSettings {
+            limit_of_messages: 5,
+            lang: Lang::new("uk"),
+            ..UserSettings::default()
+        };
+        profiles.save(ChatId(1), &settings).await.unwrap();
+        profiles.save(ChatId(1), &settings).await.unwrap();
+        let saved = profiles.get(ChatId(1)).await.unwrap();
+        assert_eq!(saved.limit_of_messages, 5);
+        assert_eq!(saved.lang, Lang::new("uk"));
+        assert_eq!(profiles.get(ChatId(2)).await.unwrap().limit_of_messages, 10);
+
+        assert!(!profiles
+            .create(ChatId(1), &UserSettings::default())
+            .await
+            .unwrap());
+        assert!(profiles
+            .create(ChatId(2), &UserSettings::default())
+            .await
+            .unwrap());
+        assert_eq!(profiles.get(ChatId(1)).await.unwrap().limit_of_messages, 5);
+    }
+
+    #[tokio::test]
+    async fn test_import_from_dialogues() {
+        let pool = test_pool().await;
+        sqlx::query(
+            "CREATE TABLE teloxide_dialogues (chat_id BIGINT PRIMARY KEY, dialogue BLOB NOT NULL)",
+        )
+        .execute(&pool)
+        .await
+        .unwrap();
+        let old = r#"{"WithIngredient":[{"name":"Bob","send_image":false,"limit_of_messages":3,"lang":"Eng"},["Vodka"]]}"#;
+        for (chat_id, dialogue) in [(1, old), (2, r#""Start""#)] {
+            sqlx::query("INSERT INTO teloxide_dialogues VALUES (?, ?)")
+                .bind(chat_id)
+                .bind(dialogue.as_bytes())
+                .execute(&pool)
+                .await
+                .unwrap();
+        }
+        let profiles = ProfileStore::new(pool).await.unwrap();
+        assert_eq!(profiles.import_from_dialogues().await.unwrap(), 1);
+        assert_eq!(profiles.import_from_dialogues().await.unwrap(), 0);
+        let imported = profiles.get(ChatId(1)).await.unwrap();
+        assert_eq!(imported.name, Some("Bob".to_string()));
+        assert_eq!(imported.limit_of_messages, 3);
+        assert_eq!(imported.lang, Lang::default());
+    }
+}
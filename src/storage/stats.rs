@@ -0,0 +1,245 @@
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex, MutexGuard};
+use std::time::Duration;
+
+use sqlx::sqlite::SqlitePool;
+use sqlx::Row;
+
+use crate::error::error_handler::ErrorHandler;
+
+/// A chat with a request within this window is active.
+const ACTIVE_WINDOW: i64 = 24 * 60 * 60;
+const FLUSH_INTERVAL: u64 = 60;
+
+#[derive(Debug, Default, PartialEq, Eq)]
+pub struct Stats {
+    pub chats: i64,
+    pub profiles: i64,
+    /// Chats with a request within `ACTIVE_WINDOW`.
+    pub active_chats: i64,
+    /// Requests per feature, the most used first.
+    pub features: Vec<(String, i64)>,
+}
+
+/// Counters which are not written yet.
+#[derive(Default)]
+struct Pending {
+    requests: HashMap<String, i64>,
+    /// Last request of a chat.
+    seen: HashMap<i64, i64>,
+}
+
+/// Usage counters. Chats are read from the dialogue and profile tables.
+/// Requests are counted in memory and written by `flush`, not on every update.
+pub struct StatsStore {
+    pool: SqlitePool,
+    pending: Mutex<Pending>,
+}
+
+impl StatsStore {
+    pub async fn new(pool: SqlitePool) -> Result<Self, ErrorHandler> {
+        sqlx::query(
+            "CREATE TABLE IF NOT EXISTS feature_requests (
+                feature TEXT PRIMARY KEY,
+                requests INTEGER NOT NULL
+            )",
+        )
+        .execute(&pool)
+        .await?;
+        sqlx::query(
+            "CREATE TABLE IF NOT EXISTS chat_activity (
+                chat_id INTEGER PRIMARY KEY,
+                seen_at INTEGER NOT NULL
+            )",
+        )
+        .execute(&pool)
+        .await?;
+        Ok(Self {
+            pool,
+            pending: Mutex::new(Pending::default()),
+        })
+    }
+
+    pub fn hit(&self, feature: &str, chat_id: Option<i64>, now: i64) {
+        let mut pending = self.pending();
+        *pending.requests.entry(feature.to_string()).or_insert(0) += 1;
+        if let Some(chat_id) = chat_id {
+            pending.seen.insert(chat_id, now);
+        }
+    }
+
+    /// Writes the counted requests in one transaction, they are kept for
+    /// the next flush when it fails.
+    pub async fn flush(&self) -> Result<(), ErrorHandler> {
+        let pending = std::mem::take(&mut *self.pending());
+        if pending.requests.is_empty() && pending.seen.is_empty() {
+            return Ok(());
+        }
+        let result = self.write(&pending).await;
+        if result.is_err() {
+            let mut current = self.pending();
+            for (feature, requests) in pending.requests {
+                *current.requests.entry(feature).or_insert(0) += requests;
+            }
+            for (chat_id, seen_at) in pending.seen {
+                let seen = current.seen.entry(chat_id).or_insert(seen_at);
+                *seen = (*seen).max(seen_at);
+            }
+        }
+        result
+    }
+
+    async fn write(&self, pending: &Pending) -> Result<(), ErrorHandler> {
+        let mut transaction = self.pool.begin().await?;
+        for (feature, requests) in pending.requests.iter() {
+            sqlx::query(
+                "INSERT INTO feature_requests (feature, requests) VALUES (?, ?)
+                ON CONFLICT(feature) DO UPDATE SET requests = requests + excluded.requests",
+            )
+            .bind(feature)
+            .bind(requests)
+            .execute(&mut transaction)
+            .await?;
+        }
+        for (chat_id, seen_at) in pending.seen.iter() {
+            sqlx::query(
+                "INSERT INTO chat_activity (chat_id, seen_at) VALUES (?, ?)
+                ON CONFLICT(chat_id) DO UPDATE SET seen_at = MAX(seen_at, excluded.seen_at)",
+            )
+            .bind(chat_id)
+            .bind(seen_at)
+            .execute(&mut transaction)
+            .await?;
+        }
+        transaction.commit().await?;
+        Ok(())
+    }
+
+    pub async fn watch(stats: Arc<StatsStore>) {
+        let mut interval = tokio::time::interval(Duration::from_secs(FLUSH_INTERVAL));
+        loop {
+            interval.tick().await;
+            if let Err(error) = stats.flush().await {
+                log::error!("Fail to save the stats: {:?}", error);
+            }
+        }
+    }
+
+    pub async fn stats(&self, now: i64) -> Result<Stats, ErrorHandler> {
+        self.flush().await?;
+        let features = sqlx::query(
+            "SELECT feature, requests FROM feature_requests ORDER BY requests DESC, feature",
+        )
+        .fetch_all(&self.pool)
+        .await?
+        .iter()
+        .map(|row| Ok((row.try_get("feature")?, row.try_get("requests")?)))
+        .collect::<Result<Vec<(String, i64)>, sqlx::Error>>()?;
+        let profiles = self.count("SELECT COUNT(*) FROM profiles").await?;
+        let chats = match self.with_dialogues().await? {
+            true => {
+                self.count(
+                    "SELECT COUNT(*) FROM (
+                        SELECT chat_id FROM teloxide_dialogues UNION SELECT chat_id FROM profiles
+                    )",
+                )
+                .await?
+            }
+            false => profiles,
+        };
+        let active_chats = sqlx::query("SELECT COUNT(*) FROM chat_activity WHERE seen_at > ?")
+            .bind(now - ACTIVE_WINDOW)
+            .fetch_one(&self.pool)
+            .await?
+            .try_get(0)?;
+        Ok(Stats {
+            chats,
+            profiles,
+            active_chats,
+            features,
+        })
+    }
+
+    /// Every chat the bot knows about.
+    pub async fn chats(&self) -> Result<Vec<i64>, ErrorHandler> {
+        let query = match self.with_dialogues().await? {
+            true => {
+                "SELECT chat_id FROM teloxide_dialogues UNION SELECT chat_id FROM profiles
+                ORDER BY chat_id"
+            }
+            false => "SELECT chat_id FROM profiles ORDER BY chat_id",
+        };
+        Ok(sqlx::query(query)
+            .fetch_all(&self.pool)
+            .await?
+            .iter()
+            .map(|row| row.try_get("chat_id"))
+            .collect::<Result<Vec<i64>, sqlx::Error>>()?)
+    }
+
+    /// The dialogue table is created by teloxide on the first dialogue.
+    async fn with_dialogues(&self) -> Result<bool, ErrorHandler> {
+        Ok(sqlx::query(
+            "SELECT name FROM sqlite_master WHERE type = 'table' AND name = 'teloxide_dialogues'",
+        )
+        .fetch_optional(&self.pool)
+        .await?
+        .is_some())
+    }
+
+    async fn count(&self, query: &str) -> Result<i64, ErrorHandler> {
+        Ok(sqlx::query(query).fetch_one(&self.pool).await?.try_get(0)?)
+    }
+
+    fn pending(&self) -> MutexGuard<'_, Pending> {
+        self.pending.lock().expect("Stats lock is poisoned.")
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use teloxide::types::ChatId;
+
+    use crate::storage::profiles::ProfileStore;
+    use crate::storage::stats::{StatsStore, ACTIVE_WINDOW};
+    use crate::telegramm::settings::settings::UserSettings;
+    use crate::utils::testing::test_pool;
+
+    #[tokio::test]
+    async fn test_stats() {
+        let pool = test_pool().await;
+        let profiles = ProfileStore::new(pool.clone()).await.unwrap();
+        let stats = StatsStore::new(pool.clone()).await.unwrap();
+        profiles
+            .save(ChatId(1), &UserSettings::default())
+            .await
+            .unwrap();
+        sqlx::query("CREATE TABLE teloxide_dialogues (chat_id INTEGER PRIMARY KEY, dialogue BLOB)")
+            .execute(&pool)
+            .await
+            .unwrap();
+        for (chat_id, dialogue) in [(1, "\"Start\""), (2, "\"FindByName\"")] {
+            sqlx::query("INSERT INTO teloxide_dialogues VALUES (?, ?)")
+                .bind(chat_id)
+                .bind(dialogue.as_bytes())
+                .execute(&pool)
+                .await
+                .unwrap();
+        }
+        stats.hit("/random", Some(1), 100);
+        stats.hit("message", Some(2), 100 + ACTIVE_WINDOW);
+        stats.flush().await.unwrap();
+        stats.hit("/random", None, 100 + ACTIVE_WINDOW);
+
+        let result = stats.stats(100 + ACTIVE_WINDOW).await.unwrap();
+        assert_eq!(result.chats, 2);
+        assert_eq!(result.profiles, 1);
+        // The first chat is idle for a day already.
+        assert_eq!(result.active_chats, 1);
+        assert_eq!(
+            result.features,
+            vec![("/random".to_string(), 2), ("message".to_string(), 1)]
+        );
+        assert_eq!(stats.chats().await.unwrap(), vec![1, 2]);
+    }
+}
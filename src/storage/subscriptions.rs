@@ -0,0 +1,183 @@
+use sqlx::sqlite::{SqlitePool, SqliteRow};
+use sqlx::Row;
+use teloxide::types::ChatId;
+
+use crate::error::error_handler::ErrorHandler;
+
+const DAY: i64 = 24 * 60 * 60;
+
+/// Daily drink for a chat at a local time.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Subscription {
+    pub chat_id: i64,
+    /// Minutes after the local midnight.
+    pub minute: i64,
+    /// Minutes from UTC.
+    pub offset: i64,
+    /// Local day of the last drink, days since the epoch.
+    pub last_day: i64,
+}
+
+impl Subscription {
+    /// The local day which still waits for its drink. A day missed while
+    /// the bot was down is caught up, older ones are not.
+    pub fn due_day(&self, now: i64) -> Option<i64> {
+        let local = now + self.offset * 60;
+        let day = local.div_euclid(DAY);
+        let minute = local.rem_euclid(DAY) / 60;
+        match minute >= self.minute && self.last_day < day {
+            true => Some(day),
+            false => None,
+        }
+    }
+}
+
+pub struct SubscriptionStore {
+    pool: SqlitePool,
+}
+
+impl SubscriptionStore {
+    pub async fn new(pool: SqlitePool) -> Result<Self, ErrorHandler> {
+        sqlx::query(
+            "CREATE TABLE IF NOT EXISTS subscriptions (
+                chat_id INTEGER PRIMARY KEY,
+                minute INTEGER NOT NULL,
+                utc_offset INTEGER NOT NULL,
+                last_day INTEGER NOT NULL DEFAULT -1
+            )",
+        )
+        .execute(&pool)
+        .await?;
+        Ok(Self { pool })
+    }
+
+    /// Changing the time keeps the day of the last drink, so it is not sent twice a day.
+    /// A new subscription starts tomorrow when today's time has already passed.
+    pub async fn subscribe(
+        &self,
+        chat_id: ChatId,
+        minute: i64,
+        offset: i64,
+        now: i64,
+    ) -> Result<(), ErrorHandler> {
+        let last_day = Subscription {
+            chat_id: chat_id.0,
+            minute,
+            offset,
+            last_day: -1,
+        }
+        .due_day(now)
+        .unwrap_or(-1);
+        sqlx::query(
+            "INSERT INTO subscriptions (chat_id, minute, utc_offset, last_day) VALUES (?, ?, ?, ?)
+            ON CONFLICT(chat_id) DO UPDATE
+            SET minute = excluded.minute, utc_offset = excluded.utc_offset",
+        )
+        .bind(chat_id.0)
+        .bind(minute)
+        .bind(offset)
+        .bind(last_day)
+        .execute(&self.pool)
+        .await?;
+        Ok(())
+    }
+
+    /// `false` when the chat was not subscribed.
+    pub async fn unsubscribe(&self, chat_id: ChatId) -> Result<bool, ErrorHandler> {
+        let result = sqlx::query("DELETE FROM subscriptions WHERE chat_id = ?")
+            .bind(chat_id.0)
+            .execute(&self.pool)
+            .await?;
+        Ok(result.rows_affected() > 0)
+    }
+
+    /// Subscriptions with the day they wait for.
+    pub async fn due(&self, now: i64) -> Result<Vec<(Subscription, i64)>, ErrorHandler> {
+        let rows = sqlx::query("SELECT * FROM subscriptions")
+            .fetch_all(&self.pool)
+            .await?;
+        let mut due = vec![];
+        for row in rows {
+            let subscription = Self::from_row(&row)?;
+            if let Some(day) = subscription.due_day(now) {
+                due.push((subscription, day));
+            }
+        }
+        Ok(due)
+    }
+
+    pub async fn mark_sent(&self, chat_id: ChatId, day: i64) -> Result<(), ErrorHandler> {
+        sqlx::query("UPDATE subscriptions SET last_day = ? WHERE chat_id = ?")
+            .bind(day)
+            .bind(chat_id.0)
+            .execute(&self.pool)
+            .await?;
+        Ok(())
+    }
+
+    fn from_row(row: &SqliteRow) -> Result<Subscription, ErrorHandler> {
+        Ok(Subscription {
+            chat_id: row.try_get("chat_id")?,
+            minute: row.try_get("minute")?,
+            offset: row.try_get("utc_offset")?,
+            last_day: row.try_get("last_day")?,
+        })
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use teloxide::types::ChatId;
+
+    use crate::storage::subscriptions::{Subscription, SubscriptionStore, DAY};
+    use crate::utils::testing::test_pool;
+
+    #[test]
+    fn test_due_day() {
+        let subscription = Subscription {
+            chat_id: 1,
+            minute: 9 * 60,
+            offset: 2 * 60,
+            last_day: 9,
+        };
+        // 06:59 UTC is 08:59 local.
+        assert_eq!(subscription.due_day(10 * DAY + 6 * 3600 + 59 * 60), None);
+        assert_eq!(subscription.due_day(10 * DAY + 7 * 3600), Some(10));
+        // 23:00 UTC is 01:00 of the next local day, too early for its drink.
+        assert_eq!(subscription.due_day(10 * DAY + 23 * 3600), None);
+        let sent = Subscription {
+            last_day: 10,
+            ..subscription
+        };
+        assert_eq!(sent.due_day(10 * DAY + 12 * 3600), None);
+    }
+
+    #[tokio::test]
+    async fn test_subscribe() {
+        let store = SubscriptionStore::new(test_pool().await).await.unwrap();
+        store.subscribe(ChatId(1), 60, 0, 4 * DAY).await.unwrap();
+        store
+            .subscribe(ChatId(2), 23 * 60, 0, 4 * DAY)
+            .await
+            .unwrap();
+        // Today's time has passed, the first drink comes tomorrow.
+        store
+            .subscribe(ChatId(3), 60, 0, 5 * DAY + 3600)
+            .await
+            .unwrap();
+
+        let now = 5 * DAY + 2 * 3600;
+        let due = store.due(now).await.unwrap();
+        assert_eq!(due.len(), 1);
+        assert_eq!(due[0].0.chat_id, 1);
+        assert_eq!(due[0].1, 5);
+
+        store.mark_sent(ChatId(1), 5).await.unwrap();
+        store.subscribe(ChatId(1), 0, 0, now).await.unwrap();
+        assert!(store.due(now).await.unwrap().is_empty());
+        assert_eq!(store.due(6 * DAY + 3600).await.unwrap().len(), 2);
+
+        assert!(store.unsubscribe(ChatId(2)).await.unwrap());
+        assert!(!store.unsubscribe(ChatId(2)).await.unwrap());
+    }
+}
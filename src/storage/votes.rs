@@ -0,0 +1,227 @@
+use serde::{Deserialize, Serialize};
+use sqlx::sqlite::{SqlitePool, SqliteRow};
+use sqlx::Row;
+
+use crate::error::error_handler::ErrorHandler;
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
+pub struct VoteOption {
+    pub id: String,
+    pub name: String,
+}
+
+#[derive(Clone, Debug)]
+pub struct Vote {
+    pub id: i64,
+    pub chat_id: i64,
+    pub message_id: i32,
+    pub options: Vec<VoteOption>,
+    /// The vote closes as soon as this many users have voted.
+    pub voters: i64,
+    pub closes_at: i64,
+}
+
+impl Vote {
+    /// The option with the most votes, the earlier option wins a tie.
+    pub fn winner(tallies: &[usize]) -> Option<usize> {
+        tallies
+            .iter()
+            .enumerate()
+            .filter(|(_, count)| **count > 0)
+            .fold(
+                None,
+                |best: Option<(usize, usize)>, (option, count)| match best {
+                    Some((_, best_count)) if best_count >= *count => best,
+                    _ => Some((option, *count)),
+                },
+            )
+            .map(|(option, _)| option)
+    }
+}
+
+/// Running votes and ballots, kept in SQLite so a restart does not lose them.
+pub struct VoteStore {
+    pool: SqlitePool,
+}
+
+impl VoteStore {
+    pub async fn new(pool: SqlitePool) -> Result<Self, ErrorHandler> {
+        sqlx::query(
+            "CREATE TABLE IF NOT EXISTS votes (
+                id INTEGER PRIMARY KEY AUTOINCREMENT,
+                chat_id INTEGER NOT NULL,
+                message_id INTEGER NOT NULL DEFAULT 0,
+                options TEXT NOT NULL,
+                voters INTEGER NOT NULL,
+                closes_at INTEGER NOT NULL,
+                closed INTEGER NOT NULL DEFAULT 0
+            )",
+        )
+        .execute(&pool)
+        .await?;
+        sqlx::query(
+            "CREATE TABLE IF NOT EXISTS vote_ballots (
+                vote_id INTEGER NOT NULL,
+                user_id INTEGER NOT NULL,
+                option INTEGER NOT NULL,
+                PRIMARY KEY (vote_id, user_id)
+            )",
+        )
+        .execute(&pool)
+        .await?;
+        Ok(Self { pool })
+    }
+
+    pub async fn create(
+        &self,
+        chat_id: i64,
+        options: &[VoteOption],
+        voters: i64,
+        closes_at: i64,
+    ) -> Result<i64, ErrorHandler> {
+        let result = sqlx::query(
+            "INSERT INTO votes (chat_id, options, voters, closes_at) VALUES (?, ?, ?, ?)",
+        )
+        .bind(chat_id)
+        .bind(serde_json::to_string(options)?)
+        .bind(voters)
+        .bind(closes_at)
+        .execute(&self.pool)
+        .await?;
+        Ok(result.last_insert_rowid())
+    }
+
+    pub async fn set_message(&self, id: i64, message_id: i32) -> Result<(), ErrorHandler> {
+        sqlx::query("UPDATE votes SET message_id = ? WHERE id = ?")
+            .bind(message_id)
+            .bind(id)
+            .execute(&self.pool)
+            .await?;
+        Ok(())
+    }
+
+    /// Open vote by id, closed votes are not returned.
+    pub async fn get(&self, id: i64) -> Result<Option<Vote>, ErrorHandler> {
+        let row = sqlx::query("SELECT * FROM votes WHERE id = ? AND closed = 0")
+            .bind(id)
+            .fetch_optional(&self.pool)
+            .await?;
+        row.map(|row| Self::from_row(&row)).transpose()
+    }
+
+    /// A user can change the vote until the vote is closed.
+    /// Returns `false` when the user voted for the same option before.
+    pub async fn cast(&self, id: i64, user_id: i64, option: usize) -> Result<bool, ErrorHandler> {
+        let result = sqlx::query(
+            "INSERT INTO vote_ballots (vote_id, user_id, option) VALUES (?, ?, ?)
+            ON CONFLICT(vote_id, user_id) DO UPDATE SET option = excluded.option
+            WHERE option != excluded.option",
+        )
+        .bind(id)
+        .bind(user_id)
+        .bind(option as i64)
+        .execute(&self.pool)
+        .await?;
+        Ok(result.rows_affected() == 1)
+    }
+
+    pub async fn tallies(&self, vote: &Vote) -> Result<Vec<usize>, ErrorHandler> {
+        let rows = sqlx::query(
+            "SELECT option, COUNT(*) AS count FROM vote_ballots WHERE vote_id = ? GROUP BY option",
+        )
+        .bind(vote.id)
+        .fetch_all(&self.pool)
+        .await?;
+        let mut tallies = vec![0; vote.options.len()];
+        for row in rows {
+            let option: i64 = row.try_get("option")?;
+            let count: i64 = row.try_get("count")?;
+            if let Some(tally) = tallies.get_mut(option as usize) {
+                *tally = count as usize;
+            }
+        }
+        Ok(tallies)
+    }
+
+    /// Votes which are open past their time.
+    pub async fn expired(&self, now: i64) -> Result<Vec<Vote>, ErrorHandler> {
+        sqlx::query("SELECT * FROM votes WHERE closed = 0 AND closes_at <= ?")
+            .bind(now)
+            .fetch_all(&self.pool)
+            .await?
+            .iter()
+            .map(Self::from_row)
+            .collect()
+    }
+
+    /// Only the first call closes the vote, so the winner is announced once.
+    pub async fn close(&self, id: i64) -> Result<bool, ErrorHandler> {
+        let result = sqlx::query("UPDATE votes SET closed = 1 WHERE id = ? AND closed = 0")
+            .bind(id)
+            .execute(&self.pool)
+            .await?;
+        Ok(result.rows_affected() == 1)
+    }
+
+    fn from_row(row: &SqliteRow) -> Result<Vote, ErrorHandler> {
+        Ok(Vote {
+            id: row.try_get("id")?,
+            chat_id: row.try_get("chat_id")?,
+            message_id: row.try_get("message_id")?,
+            options: serde_json::from_str(&row.try_get::<String, _>("options")?)?,
+            voters: row.try_get("voters")?,
+            closes_at: row.try_get("closes_at")?,
+        })
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::storage::votes::{Vote, VoteOption, VoteStore};
+    use crate::utils::testing::test_pool;
+
+    async fn store() -> VoteStore {
+        VoteStore::new(test_pool().await).await.unwrap()
+    }
+
+    fn options() -> Vec<VoteOption> {
+        ["Mojito", "Margarita", "Negroni"]
+            .iter()
+            .enumerate()
+            .map(|(id, name)| VoteOption {
+                id: id.to_string(),
+                name: name.to_string(),
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_winner() {
+        assert_eq!(Vote::winner(&[1, 3, 3]), Some(1));
+        assert_eq!(Vote::winner(&[2, 0, 1]), Some(0));
+        assert_eq!(Vote::winner(&[0, 0, 0]), None);
+    }
+
+    #[tokio::test]
+    async fn test_vote() {
+        let votes = store().await;
+        let id = votes.create(-1, &options(), 3, 100).await.unwrap();
+        votes.set_message(id, 42).await.unwrap();
+        let vote = votes.get(id).await.unwrap().unwrap();
+        assert_eq!(vote.message_id, 42);
+        assert_eq!(vote.options, options());
+
+        assert!(votes.cast(id, 1, 0).await.unwrap());
+        assert!(votes.cast(id, 2, 2).await.unwrap());
+        assert!(votes.cast(id, 1, 2).await.unwrap());
+        assert!(!votes.cast(id, 1, 2).await.unwrap());
+        assert_eq!(votes.tallies(&vote).await.unwrap(), vec![0, 0, 2]);
+
+        assert!(votes.expired(99).await.unwrap().is_empty());
+        assert_eq!(votes.expired(100).await.unwrap().len(), 1);
+        assert!(votes.close(id).await.unwrap());
+        assert!(!votes.close(id).await.unwrap());
+        assert!(votes.get(id).await.unwrap().is_none());
+        assert!(votes.expired(100).await.unwrap().is_empty());
+    }
+}
@@ -1,25 +1,46 @@
+use std::sync::Arc;
+
 use itertools::Itertools;
-use teloxide::payloads::SendMessageSetters;
-use teloxide::prelude::{AutoSend, CallbackQuery, Requester};
-use teloxide::types::InputFile;
+use teloxide::payloads::{
+    AnswerCallbackQuerySetters, EditMessageReplyMarkupSetters, EditMessageTextSetters,
+    SendMessageSetters,
+};
+use teloxide::prelude::{AutoSend, CallbackQuery, Message, Requester};
+use teloxide::types::{
+    ChatId, InlineKeyboardButton, InlineKeyboardButtonKind, InlineKeyboardMarkup, InputFile, User,
+};
 use teloxide::Bot;
 use url::Url;
 
 use crate::cocktails_api::schemas::drink::LangDrink;
 use crate::cocktails_api::schemas::ToLangDrink;
 use crate::cocktails_api::services::coctail_service::DrinksService;
+use crate::game::engine::{Game, Step};
+use crate::game::questions;
+use crate::game::questions::{Answer, Question, Topic};
 use crate::localization::lang::Lang;
+use crate::storage::history::{Event, HistoryStore};
+use crate::storage::profiles::ProfileStore;
+use crate::storage::votes::VoteStore;
+use crate::telegramm::buttons::inline::{actions_keyboard, ingredients_keyboard, CallbackData};
 use crate::telegramm::buttons::keyboard::{make_keyboard, Keyboard};
+use crate::telegramm::commands::favorites::FavoritesHandler;
 use crate::telegramm::commands::func::CommandsHandler;
-use crate::telegramm::messages::message_handler::TELEGRAMM_CHAR_LIMIT;
+use crate::telegramm::commands::recommend::RecommendHandler;
+use crate::telegramm::commands::scale::ScaleHandler;
+use crate::telegramm::commands::shopping::ShoppingHandler;
+use crate::telegramm::commands::vote::VoteHandler;
+use crate::telegramm::messages::group::GroupHandler;
+use crate::telegramm::messages::message_handler::{MessageHandler, TELEGRAMM_CHAR_LIMIT};
 use crate::telegramm::settings::settings::{SettingsKeyboard, UserSettings};
 use crate::telegramm::state::State;
 use crate::telegramm::{LocalDialogue, ReturnTy};
 use crate::utils::helpers;
-use crate::utils::helpers::random_num_in_range;
 use crate::utils::unicod::Emojis;
 use crate::{ErrorHandler, ErrorType};
 
+const POPULAR_INGREDIENTS: usize = 12;
+
 pub struct CallBackHandler;
 
 impl CallBackHandler {
@@ -27,52 +48,97 @@ impl CallBackHandler {
         bot: AutoSend<Bot>,
         callback: CallbackQuery,
         dialogue: LocalDialogue,
+        service: Arc<DrinksService>,
+        profiles: Arc<ProfileStore>,
+        history: Arc<HistoryStore>,
     ) -> ReturnTy {
         if let Some(response) = callback.data {
-            let UserSettings { lang, .. } = CommandsHandler::get_settings(&dialogue).await?;
-            match Keyboard::try_from(Self::to_button(&response, &lang))? {
-                Keyboard::FindCocktail => Self::find_by_name(bot, dialogue).await?,
-                Keyboard::FindIngredient => Self::find_ingredient(bot, dialogue).await?,
-                Keyboard::Ingredients => Self::all_ingredients(bot, dialogue).await?,
-                Keyboard::Categories => Self::all_category(bot, dialogue).await?,
-                Keyboard::WithThisIngredient => Self::with_this_ingredient(bot, dialogue).await?,
-                Keyboard::WithThisCategory => Self::with_this_category(bot, dialogue).await?,
-                Keyboard::DrinkForYou => Self::cocktail_for_you(bot, dialogue).await?,
-                Keyboard::Settings => Self::settings(bot, dialogue).await?,
+            let UserSettings { lang, .. } = profiles.get(dialogue.chat_id()).await?;
+            // Buttons of an older menu in another language are ignored.
+            let button = match Self::to_button(&response, &lang) {
+                Some(button) => button,
+                None => return Ok(()),
+            };
+            match Keyboard::try_from(button)? {
+                Keyboard::FindCocktail => Self::find_by_name(bot, dialogue, profiles).await?,
+                Keyboard::FindIngredient => Self::find_ingredient(bot, dialogue, profiles).await?,
+                Keyboard::Ingredients => {
+                    Self::all_ingredients(bot, dialogue, service, profiles).await?
+                }
+                Keyboard::Categories => {
+                    Self::all_category(bot, dialogue, service, profiles).await?
+                }
+                Keyboard::WithThisIngredient => {
+                    Self::with_this_ingredient(bot, dialogue, service, profiles).await?
+                }
+                Keyboard::WithThisCategory => {
+                    Self::with_this_category(bot, dialogue, profiles).await?
+                }
+                Keyboard::DrinkForYou => {
+                    let user = GroupHandler::user_chat(&callback.from);
+                    Self::cocktail_for_you(bot, dialogue, service, profiles, history, user).await?
+                }
+                Keyboard::WhatCanIMake => {
+                    let user = GroupHandler::user_chat(&callback.from);
+                    Self::what_can_i_make(bot, dialogue, service, profiles, history, user).await?
+                }
+                Keyboard::Recommended => {
+                    let user = GroupHandler::user_chat(&callback.from);
+                    RecommendHandler::recommend(
+                        &bot, &dialogue, &service, &profiles, &history, user,
+                    )
+                    .await?
+                }
+                Keyboard::Settings => {
+                    Self::settings(bot, dialogue, profiles, &callback.from).await?
+                }
             }
         }
         Ok(())
     }
-    fn to_button(response: &str, lang: &Lang) -> String {
-        let (key, _) = lang
-            .buttons
+    fn to_button(response: &str, lang: &Lang) -> Option<String> {
+        lang.buttons
             .main
             .iter()
             .find(|(_, value)| *value == response)
-            .expect("Unreachable code.");
-        key.clone()
+            .map(|(key, _)| key.clone())
     }
     pub async fn callback_settings(
         bot: AutoSend<Bot>,
         dialogue: LocalDialogue,
         callback: CallbackQuery,
+        profiles: Arc<ProfileStore>,
     ) -> ReturnTy {
-        if let Some(callback) = callback.data {
-            let mut user_settings = CommandsHandler::get_settings(&dialogue).await?;
-            let button_key = Self::to_setting_button(&callback, &user_settings.lang)?;
-            match SettingsKeyboard::try_from(button_key.as_str())? {
+        if let Some(data) = callback.data {
+            let mut user_settings = profiles.get(dialogue.chat_id()).await?;
+            let button_key = Self::to_setting_button(&data, &user_settings.lang)?;
+            let button = SettingsKeyboard::try_from(button_key.as_str())?;
+            if !matches!(button, SettingsKeyboard::Back)
+                && !GroupHandler::can_configure(&bot, dialogue.chat_id(), &callback.from).await?
+            {
+                bot.send_message(
+                    dialogue.chat_id(),
+                    &user_settings.lang.fail_messages.admins_only,
+                )
+                .await?;
+                return Ok(());
+            }
+            match button {
                 SettingsKeyboard::Back => {
-                    return CommandsHandler::start_commands(&bot, &dialogue).await;
+                    return CommandsHandler::start_commands(&bot, &dialogue, &profiles).await;
+                }
+                SettingsKeyboard::Units => {
+                    user_settings.units = user_settings.units.next();
+                    profiles.save(dialogue.chat_id(), &user_settings).await?;
+                    Self::send_setting_message(&bot, &dialogue, &user_settings).await?;
                 }
                 SettingsKeyboard::Images => {
                     user_settings.send_image = match &user_settings.send_image {
                         true => false,
                         false => true,
                     };
-                    dialogue
-                        .update(State::Settings(user_settings.clone()))
-                        .await?;
-                    Self::send_setting_message(&bot, &dialogue, &user_settings.lang).await?;
+                    profiles.save(dialogue.chat_id(), &user_settings).await?;
+                    Self::send_setting_message(&bot, &dialogue, &user_settings).await?;
                 }
                 SettingsKeyboard::MessageLimit => {
                     bot.send_message(
@@ -81,34 +147,33 @@ impl CallBackHandler {
                     )
                     .await?;
                     dialogue
-                        .update(State::SettingsUpdate(
-                            CommandsHandler::get_settings(&dialogue).await?,
-                            SettingsKeyboard::MessageLimit,
-                        ))
+                        .update(State::SettingsUpdate(SettingsKeyboard::MessageLimit))
                         .await?;
                 }
                 SettingsKeyboard::Name => {
                     bot.send_message(
                         dialogue.chat_id(),
-                        &format!(" -{} \n", &user_settings.lang.todo.settings_set_name),
+                        format!(" -{} \n", &user_settings.lang.todo.settings_set_name),
                     )
                     .await?;
                     dialogue
-                        .update(State::SettingsUpdate(
-                            CommandsHandler::get_settings(&dialogue).await?,
-                            SettingsKeyboard::Name,
-                        ))
+                        .update(State::SettingsUpdate(SettingsKeyboard::Name))
                         .await?;
                 }
                 SettingsKeyboard::Lang => {
-                    user_settings.lang = match &user_settings.lang {
-                        Lang::Eng => Lang::Ukr,
-                        Lang::Ukr => Lang::Eng,
-                    };
-                    dialogue
-                        .update(State::Settings(user_settings.clone()))
-                        .await?;
-                    Self::send_setting_message(&bot, &dialogue, &user_settings.lang).await?;
+                    let lang = &user_settings.lang;
+                    let languages = Lang::available()
+                        .iter()
+                        .map(|option| CallbackData::Lang(option.code().to_string()).button(lang))
+                        .collect::<Vec<InlineKeyboardButton>>();
+                    bot.send_message(
+                        dialogue.chat_id(),
+                        format!("{} {}", lang.settings_descriptions.lang, lang.name),
+                    )
+                    .reply_markup(InlineKeyboardMarkup::new(
+                        languages.chunks(2).map(<[InlineKeyboardButton]>::to_vec),
+                    ))
+                    .await?;
                 }
             };
         }
@@ -127,23 +192,29 @@ impl CallBackHandler {
         Ok(key.clone())
     }
 
-    async fn settings(bot: AutoSend<Bot>, dialogue: LocalDialogue) -> ReturnTy {
-        let UserSettings { lang, .. } = CommandsHandler::get_settings(&dialogue).await?;
-        Self::send_setting_message(&bot, &dialogue, &lang).await?;
-        dialogue
-            .update(State::Settings(
-                CommandsHandler::get_settings(&dialogue).await?,
-            ))
-            .await?;
+    async fn settings(
+        bot: AutoSend<Bot>,
+        dialogue: LocalDialogue,
+        profiles: Arc<ProfileStore>,
+        user: &User,
+    ) -> ReturnTy {
+        let settings = profiles.get(dialogue.chat_id()).await?;
+        if !GroupHandler::can_configure(&bot, dialogue.chat_id(), user).await? {
+            bot.send_message(dialogue.chat_id(), &settings.lang.fail_messages.admins_only)
+                .await?;
+            return Ok(());
+        }
+        Self::send_setting_message(&bot, &dialogue, &settings).await?;
+        dialogue.update(State::Settings).await?;
         Ok(())
     }
 
     async fn send_setting_message(
         bot: &AutoSend<Bot>,
         dialogue: &LocalDialogue,
-        lang: &Lang,
+        settings: &UserSettings,
     ) -> ReturnTy {
-        let settings = CommandsHandler::get_settings(dialogue).await?;
+        let lang = &settings.lang;
         let keyboard = SettingsKeyboard::as_array()
             .iter()
             .map(|key| {
@@ -161,202 +232,549 @@ impl CallBackHandler {
         Ok(())
     }
 
-    async fn cocktail_for_you(bot: AutoSend<Bot>, dialogue: LocalDialogue) -> ReturnTy {
+    async fn cocktail_for_you(
+        bot: AutoSend<Bot>,
+        dialogue: LocalDialogue,
+        service: Arc<DrinksService>,
+        profiles: Arc<ProfileStore>,
+        history: Arc<HistoryStore>,
+        user: ChatId,
+    ) -> ReturnTy {
         let random_char_of_eng_alphabet = helpers::random_english_character()?;
-        let user_settings: UserSettings = CommandsHandler::get_settings(&dialogue).await?;
-        if let Some(result) = DrinksService::search_by_first_letter(
-            &random_char_of_eng_alphabet,
-            user_settings.lang.clone(),
-        )
-        .await?
+        let user_settings: UserSettings = profiles.get(dialogue.chat_id()).await?;
+        if let Some(result) = service
+            .search_by_first_letter(
+                &random_char_of_eng_alphabet,
+                user_settings.lang.clone(),
+                user_settings.units,
+            )
+            .await?
         {
-            let result = Self::make_less(result);
-            let alcohol = Self::ingredients_as_str_vec(&result);
-            let keyboard = make_keyboard(&alcohol[0..2]);
-            bot.send_message(dialogue.chat_id(), &user_settings.lang.todo.game_choose)
-                .reply_markup(keyboard)
-                .await?;
+            if let Some(game) = Game::new(result, rand::random()) {
+                Self::play(&bot, &dialogue, &profiles, &history, user, game).await?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Returns the notice when the game is already over.
+    async fn game(
+        bot: &AutoSend<Bot>,
+        dialogue: &LocalDialogue,
+        profiles: &ProfileStore,
+        history: &HistoryStore,
+        user: ChatId,
+        answer: Answer,
+    ) -> Result<Option<String>, ErrorHandler> {
+        let mut game = match dialogue.get().await? {
+            Some(State::CocktailForYou(game)) => game,
+            _ => {
+                let settings = profiles.get(dialogue.chat_id()).await?;
+                return Ok(Some(settings.lang.fail_messages.expired.clone()));
+            }
+        };
+        // Buttons of an older question are ignored.
+        if game.choose(answer) {
+            Self::play(bot, dialogue, profiles, history, user, game).await?;
+        }
+        Ok(None)
+    }
 
-            dialogue
-                .update(State::CocktailForYou {
-                    game: (alcohol[0].to_string(), alcohol[1].to_string()),
-                    all: result,
-                    settings: user_settings,
-                })
+    /// Asks the next question or sends the result.
+    async fn play(
+        bot: &AutoSend<Bot>,
+        dialogue: &LocalDialogue,
+        profiles: &ProfileStore,
+        history: &HistoryStore,
+        user: ChatId,
+        game: Game<LangDrink>,
+    ) -> ReturnTy {
+        let settings = profiles.get(dialogue.chat_id()).await?;
+        match game.step() {
+            Step::Ask(question) => {
+                let (text, keyboard) = Self::question_message(question, &settings.lang);
+                bot.send_message(dialogue.chat_id(), text)
+                    .reply_markup(keyboard)
+                    .await?;
+                dialogue.update(State::CocktailForYou(game.clone())).await?;
+            }
+            Step::Done(drink) => {
+                if let Some(id) = &drink.get_drink().id {
+                    history.record(user, id, Event::Played).await?;
+                }
+                Self::send_message_with_keyboard(
+                    &drink.to_string(),
+                    actions_keyboard(std::slice::from_ref(drink), &settings.lang),
+                    bot,
+                    dialogue,
+                )
                 .await?;
+                if settings.send_image {
+                    if let Some(url) = &drink.get_drink().image {
+                        bot.send_photo(dialogue.chat_id(), InputFile::url(Url::parse(url)?))
+                            .await?;
+                    }
+                }
+                CommandsHandler::start_commands(bot, dialogue, profiles).await?;
+            }
         }
         Ok(())
     }
-    fn make_less(vec: Vec<LangDrink>) -> Vec<LangDrink> {
-        vec.into_iter()
-            .enumerate()
-            .filter_map(|(num, drink)| match num % 2 == 0 {
-                true => Some(drink),
-                _ => None,
-            })
-            .collect::<Vec<LangDrink>>()
-    }
 
-    fn ingredients_as_str_vec(raw_drink: &[LangDrink]) -> Vec<&str> {
-        raw_drink
+    fn question_message(question: &Question, lang: &Lang) -> (String, InlineKeyboardMarkup) {
+        let game = &lang.game;
+        let text = match question.topic {
+            Topic::Alcohol => &game.alcohol,
+            Topic::Category => &game.category,
+            Topic::Glass => &game.glass,
+            Topic::Ingredient => &game.ingredient,
+            Topic::Strength => &game.strength,
+            Topic::Sweetness => &game.sweetness,
+        };
+        let mut keyboard = question
+            .options
             .iter()
-            .filter_map(|drink| match &drink.get_drink().ingredients.get(0) {
-                None => None,
-                Some(_) => {
-                    let random_ingredient =
-                        random_num_in_range(0, drink.get_drink().ingredients.len());
-                    Some(
-                        drink
-                            .get_drink()
-                            .ingredients
-                            .get(random_ingredient)
-                            .expect("Unreachable code.")
-                            .0
-                            .as_ref(),
-                    )
-                }
+            .enumerate()
+            .map(|(num, value)| {
+                let label = match value.as_str() {
+                    questions::ALCO => game.alco.clone(),
+                    questions::NON_ALCO => game.non_alco.clone(),
+                    questions::STRONG => game.strong.clone(),
+                    questions::LIGHT => game.light.clone(),
+                    questions::SWEET => game.sweet.clone(),
+                    questions::DRY => game.dry.clone(),
+                    _ => match question.topic {
+                        Topic::Glass => lang.names.glass(value),
+                        Topic::Ingredient => lang.names.ingredient(value),
+                        _ => lang.names.category(value),
+                    },
+                };
+                InlineKeyboardButton::callback(
+                    label,
+                    CallbackData::Game(Answer::Option(num)).to_string(),
+                )
             })
-            .unique()
-            .collect::<Vec<&str>>()
+            .chunks(2)
+            .into_iter()
+            .map(Iterator::collect)
+            .collect::<Vec<Vec<InlineKeyboardButton>>>();
+        keyboard.push(vec![
+            InlineKeyboardButton::callback(
+                &game.either,
+                CallbackData::Game(Answer::Either).to_string(),
+            ),
+            InlineKeyboardButton::callback(
+                &game.skip,
+                CallbackData::Game(Answer::Skip).to_string(),
+            ),
+        ]);
+        (text.clone(), InlineKeyboardMarkup::new(keyboard))
     }
 
-    pub async fn game(
+    async fn what_can_i_make(
         bot: AutoSend<Bot>,
-        callback: CallbackQuery,
         dialogue: LocalDialogue,
+        service: Arc<DrinksService>,
+        profiles: Arc<ProfileStore>,
+        history: Arc<HistoryStore>,
+        user: ChatId,
     ) -> ReturnTy {
-        if let Some(callback) = callback.data {
-            if let State::CocktailForYou {
-                mut all,
-                game: (first, second),
-                settings,
-            } = dialogue.get().await?.ok_or(ErrorHandler {
-                msg: "Absents of State in dialogue.".to_string(),
-                ty: ErrorType::Unexpected,
-            })? {
-                let callback = if callback == first { second } else { first };
-                Self::filter(&callback, &mut all);
-                let drink_str = Self::ingredients_as_str_vec(&all);
-
-                if drink_str.len() >= 2 {
-                    let keyboard = make_keyboard(&drink_str[0..2]);
-                    bot.send_message(dialogue.chat_id(), &settings.lang.todo.game_choose)
-                        .reply_markup(keyboard)
-                        .await?;
-                    dialogue
-                        .update(State::CocktailForYou {
-                            game: (drink_str[0].to_string(), drink_str[1].to_string()),
-                            all,
-                            settings,
-                        })
-                        .await?;
-                } else {
-                    let drink = all.get(0).ok_or(ErrorHandler {
-                        msg: "Exception in Game algorithm.".to_string(),
-                        ty: ErrorType::Unexpected,
-                    })?;
-                    bot.send_message(dialogue.chat_id(), &drink.to_string())
-                        .await?;
-                    if settings.send_image {
-                        if let Some(url) = &drink.get_drink().image {
-                            bot.send_photo(dialogue.chat_id(), InputFile::url(Url::parse(url)?))
-                                .await?;
-                        }
-                    }
-                    CommandsHandler::start_commands(&bot, &dialogue).await?;
-                }
-            }
+        let UserSettings {
+            lang, bar, units, ..
+        } = profiles.get(user).await?;
+        if bar.is_empty() {
+            bot.send_message(dialogue.chat_id(), &lang.bar.empty)
+                .await?;
+            return CommandsHandler::start_commands(&bot, &dialogue, &profiles).await;
+        }
+        let result = service.what_can_i_make(&bar, lang.clone(), units).await?;
+        if result.is_empty() {
+            bot.send_message(dialogue.chat_id(), &lang.bar.nothing)
+                .await?;
+            CommandsHandler::start_commands(&bot, &dialogue, &profiles).await?;
+        } else {
+            MessageHandler::send_vec_with_photo(
+                &result, &bot, &dialogue, &profiles, &history, user,
+            )
+            .await?;
         }
-
         Ok(())
     }
 
-    fn filter(filter: &str, vec: &mut Vec<LangDrink>) {
-        vec.retain(|drink| {
-            !drink
-                .get_drink()
-                .ingredients
-                .iter()
-                .any(|(ingr, _)| ingr == filter)
-        });
-    }
-
-    async fn with_this_category(bot: AutoSend<Bot>, dialogue: LocalDialogue) -> ReturnTy {
-        let user_settings: UserSettings = CommandsHandler::get_settings(&dialogue).await?;
+    async fn with_this_category(
+        bot: AutoSend<Bot>,
+        dialogue: LocalDialogue,
+        profiles: Arc<ProfileStore>,
+    ) -> ReturnTy {
+        let user_settings: UserSettings = profiles.get(dialogue.chat_id()).await?;
         let message = format!(
             "{}{}, \n{} ",
             &user_settings.name.as_ref().unwrap_or(&"".to_string()),
             Emojis::Smile.random()?,
             &user_settings.lang.todo.category_write,
         );
-        dialogue.update(State::WithCategory(user_settings)).await?;
+        dialogue.update(State::WithCategory).await?;
         bot.send_message(dialogue.chat_id(), message).await?;
         Ok(())
     }
 
-    async fn with_this_ingredient(bot: AutoSend<Bot>, dialogue: LocalDialogue) -> ReturnTy {
-        let user_settings: UserSettings = CommandsHandler::get_settings(&dialogue).await?;
+    async fn with_this_ingredient(
+        bot: AutoSend<Bot>,
+        dialogue: LocalDialogue,
+        service: Arc<DrinksService>,
+        profiles: Arc<ProfileStore>,
+    ) -> ReturnTy {
+        let user_settings: UserSettings = profiles.get(dialogue.chat_id()).await?;
         let message = format!(
-            "{}{}. \n{}",
+            "{}{}. \n{}\n{}",
             &user_settings.name.as_ref().unwrap_or(&"".to_string()),
             Emojis::Smile.random()?,
             &user_settings.lang.todo.ingredient_write,
+            &user_settings.lang.todo.ingredients_pick,
         );
-        dialogue
-            .update(State::WithIngredient(user_settings))
+        let popular = Self::popular_ingredients(&service, &user_settings.lang).await?;
+        let keyboard = ingredients_keyboard(&popular, &[], &user_settings.lang);
+        dialogue.update(State::WithIngredient(vec![])).await?;
+        bot.send_message(dialogue.chat_id(), message)
+            .reply_markup(keyboard)
             .await?;
-        bot.send_message(dialogue.chat_id(), message).await?;
         Ok(())
     }
 
-    async fn find_by_name(bot: AutoSend<Bot>, dialogue: LocalDialogue) -> ReturnTy {
-        let user_settings: UserSettings = CommandsHandler::get_settings(&dialogue).await?;
+    /// Offered as toggle buttons, which refer to them by index.
+    async fn popular_ingredients(
+        service: &DrinksService,
+        lang: &Lang,
+    ) -> Result<Vec<String>, ErrorHandler> {
+        Ok(service
+            .get_all_ingredients(lang.clone())
+            .await?
+            .into_iter()
+            .take(POPULAR_INGREDIENTS)
+            .map(|ingredient| ingredient.list.name)
+            .collect())
+    }
+
+    async fn toggle_ingredient(
+        bot: &AutoSend<Bot>,
+        dialogue: &LocalDialogue,
+        service: &DrinksService,
+        lang: &Lang,
+        message: Option<Message>,
+        index: usize,
+    ) -> ReturnTy {
+        if let Some(State::WithIngredient(mut selected)) = dialogue.get().await? {
+            let popular = Self::popular_ingredients(service, lang).await?;
+            let name = match popular.get(index) {
+                Some(name) => name.clone(),
+                None => return Ok(()),
+            };
+            match selected.iter().position(|picked| *picked == name) {
+                Some(position) => {
+                    selected.remove(position);
+                }
+                None => selected.push(name),
+            }
+            if let Some(message) = message {
+                bot.edit_message_reply_markup(dialogue.chat_id(), message.id)
+                    .reply_markup(ingredients_keyboard(&popular, &selected, lang))
+                    .await?;
+            }
+            dialogue.update(State::WithIngredient(selected)).await?;
+        }
+        Ok(())
+    }
+
+    async fn find_by_name(
+        bot: AutoSend<Bot>,
+        dialogue: LocalDialogue,
+        profiles: Arc<ProfileStore>,
+    ) -> ReturnTy {
+        let user_settings: UserSettings = profiles.get(dialogue.chat_id()).await?;
         let message = format!(
             " {}{} \n{}",
             &user_settings.name.as_ref().unwrap_or(&"".to_string()),
             Emojis::Smile.random()?,
             &user_settings.lang.todo.find_cocktails_write
         );
-        dialogue.update(State::FindByName(user_settings)).await?;
+        dialogue.update(State::FindByName).await?;
         bot.send_message(dialogue.chat_id(), message).await?;
         Ok(())
     }
 
-    async fn find_ingredient(bot: AutoSend<Bot>, dialogue: LocalDialogue) -> ReturnTy {
-        let user_settings: UserSettings = CommandsHandler::get_settings(&dialogue).await?;
+    async fn find_ingredient(
+        bot: AutoSend<Bot>,
+        dialogue: LocalDialogue,
+        profiles: Arc<ProfileStore>,
+    ) -> ReturnTy {
+        let user_settings: UserSettings = profiles.get(dialogue.chat_id()).await?;
         let message = format!(
             "{}{} \n{} ",
             &user_settings.name.as_ref().unwrap_or(&"".to_string()),
             Emojis::Smile.random()?,
             &user_settings.lang.todo.find_ingredient_write,
         );
-        dialogue
-            .update(State::FindIngrByName(user_settings))
-            .await?;
+        dialogue.update(State::FindIngrByName).await?;
         bot.send_message(dialogue.chat_id(), message).await?;
         Ok(())
     }
 
-    async fn all_ingredients(bot: AutoSend<Bot>, dialogue: LocalDialogue) -> ReturnTy {
-        let UserSettings { lang, .. } = CommandsHandler::get_settings(&dialogue).await?;
+    async fn all_ingredients(
+        bot: AutoSend<Bot>,
+        dialogue: LocalDialogue,
+        service: Arc<DrinksService>,
+        profiles: Arc<ProfileStore>,
+    ) -> ReturnTy {
+        let UserSettings { lang, .. } = profiles.get(dialogue.chat_id()).await?;
         let result = helpers::vec_to_string(
-            DrinksService::get_all_ingredients(lang).await?.as_slice(),
+            service.get_all_ingredients(lang).await?.as_slice(),
             "\n_______________________\n",
         );
         Self::send_message(&result, &bot, &dialogue).await?;
-        CommandsHandler::start_commands(&bot, &dialogue).await?;
+        CommandsHandler::start_commands(&bot, &dialogue, &profiles).await?;
         Ok(())
     }
 
-    async fn all_category(bot: AutoSend<Bot>, dialogue: LocalDialogue) -> ReturnTy {
-        let UserSettings { lang, .. } = CommandsHandler::get_settings(&dialogue).await?;
+    async fn all_category(
+        bot: AutoSend<Bot>,
+        dialogue: LocalDialogue,
+        service: Arc<DrinksService>,
+        profiles: Arc<ProfileStore>,
+    ) -> ReturnTy {
+        let UserSettings { lang, .. } = profiles.get(dialogue.chat_id()).await?;
         let result = helpers::vec_to_string(
-            &DrinksService::get_all_category(lang).await?,
+            &service.get_all_category(lang).await?,
             "\n_______________________\n",
         );
 
         Self::send_message(&result, &bot, &dialogue).await?;
-        CommandsHandler::start_commands(&bot, &dialogue).await?;
+        CommandsHandler::start_commands(&bot, &dialogue, &profiles).await?;
+        Ok(())
+    }
+
+    #[allow(clippy::too_many_arguments)]
+    pub async fn inline_action(
+        bot: AutoSend<Bot>,
+        callback: CallbackQuery,
+        dialogue: LocalDialogue,
+        service: Arc<DrinksService>,
+        profiles: Arc<ProfileStore>,
+        votes: Arc<VoteStore>,
+        history: Arc<HistoryStore>,
+        action: CallbackData,
+    ) -> ReturnTy {
+        let UserSettings { lang, units, .. } = profiles.get(dialogue.chat_id()).await?;
+        let user = GroupHandler::user_chat(&callback.from);
+        let mut notice = None;
+        match action {
+            CallbackData::Details(id) => {
+                let drink = match service.lookup_by_id(&id, lang.clone(), units).await? {
+                    Some(drink) => drink,
+                    None => {
+                        bot.send_message(dialogue.chat_id(), &lang.fail_messages.non_results)
+                            .await?;
+                        return Ok(());
+                    }
+                };
+                history.record(user, &id, Event::Viewed).await?;
+                let expanded = match &callback.message {
+                    Some(message) if Self::expands_in_place(message) => {
+                        let mut edit = bot.edit_message_text(
+                            dialogue.chat_id(),
+                            message.id,
+                            drink.to_string(),
+                        );
+                        if let Some(keyboard) =
+                            actions_keyboard(std::slice::from_ref(&drink), &lang)
+                        {
+                            edit = edit.reply_markup(keyboard);
+                        }
+                        edit.await?;
+                        true
+                    }
+                    Some(message) => {
+                        MessageHandler::expand_in_page(&bot, &dialogue, &lang, message, &drink)
+                            .await?
+                    }
+                    None => false,
+                };
+                if !expanded {
+                    Self::send_message_with_keyboard(
+                        &drink.to_string(),
+                        actions_keyboard(std::slice::from_ref(&drink), &lang),
+                        &bot,
+                        &dialogue,
+                    )
+                    .await?
+                }
+            }
+            CallbackData::Toggle(index) => {
+                Self::toggle_ingredient(&bot, &dialogue, &service, &lang, callback.message, index)
+                    .await?
+            }
+            CallbackData::BarAdd(name) => {
+                CommandsHandler::bar_add(&bot, &dialogue, &profiles, user, &name).await?
+            }
+            CallbackData::Search => {
+                if let Some(State::WithIngredient(selected)) = dialogue.get().await? {
+                    if !selected.is_empty() {
+                        MessageHandler::send_matches(
+                            selected, &bot, &dialogue, &service, &profiles, &history, user,
+                        )
+                        .await?;
+                    }
+                }
+            }
+            CallbackData::Save(id) => {
+                notice = Some(FavoritesHandler::save(&service, &profiles, user, &id).await?);
+                history.record(user, &id, Event::Saved).await?;
+            }
+            CallbackData::FavoritesPage(page) => {
+                FavoritesHandler::show_page(
+                    &bot,
+                    &dialogue,
+                    &profiles,
+                    user,
+                    callback.message,
+                    page,
+                )
+                .await?
+            }
+            CallbackData::Game(answer) => {
+                notice = Self::game(&bot, &dialogue, &profiles, &history, user, answer).await?;
+            }
+            CallbackData::Page(page) => {
+                notice = MessageHandler::turn_page(
+                    &bot,
+                    &dialogue,
+                    &profiles,
+                    &history,
+                    user,
+                    callback.message,
+                    page,
+                )
+                .await?;
+            }
+            CallbackData::FavoritesRemove(page, id) => {
+                notice = Some(
+                    FavoritesHandler::remove(
+                        &bot,
+                        &dialogue,
+                        &profiles,
+                        user,
+                        callback.message,
+                        page,
+                        &id,
+                    )
+                    .await?,
+                );
+            }
+            CallbackData::Vote(id, option) => {
+                notice = Some(
+                    VoteHandler::cast(
+                        &bot,
+                        &service,
+                        &profiles,
+                        &votes,
+                        &callback.from,
+                        id,
+                        option,
+                    )
+                    .await?,
+                );
+            }
+            CallbackData::NotForMe(id) => {
+                notice = Some(RecommendHandler::not_for_me(&profiles, &history, user, &id).await?);
+            }
+            CallbackData::Lang(code) => {
+                match GroupHandler::can_configure(&bot, dialogue.chat_id(), &callback.from).await? {
+                    true => {
+                        let mut settings = profiles.get(dialogue.chat_id()).await?;
+                        settings.lang = Lang::new(&code);
+                        profiles.save(dialogue.chat_id(), &settings).await?;
+                        Self::send_setting_message(&bot, &dialogue, &settings).await?;
+                        dialogue.update(State::Settings).await?;
+                    }
+                    false => notice = Some(lang.fail_messages.admins_only.clone()),
+                }
+            }
+            CallbackData::Scale(id) => ScaleHandler::menu(&bot, &dialogue, &profiles, &id).await?,
+            CallbackData::Servings(id, servings) => {
+                ScaleHandler::send_servings(&bot, &dialogue, &service, &profiles, &id, servings)
+                    .await?
+            }
+            CallbackData::ScaleCustom(id) => {
+                ScaleHandler::ask(&bot, &dialogue, &profiles, State::Servings(id)).await?
+            }
+            CallbackData::Batch(id) => {
+                ScaleHandler::ask(&bot, &dialogue, &profiles, State::Batch(id)).await?
+            }
+            CallbackData::Shop(id) => {
+                notice = Some(ShoppingHandler::add(&service, &profiles, user, &id).await?)
+            }
+            CallbackData::ShopFavorites => {
+                ShoppingHandler::add_favorites(&bot, &dialogue, &profiles, user).await?
+            }
+            CallbackData::ShopSend => {
+                ShoppingHandler::send(&bot, &dialogue, &service, &profiles, user, false).await?
+            }
+            CallbackData::ShopFile => {
+                ShoppingHandler::send(&bot, &dialogue, &service, &profiles, user, true).await?
+            }
+            CallbackData::ShopClear => {
+                notice = Some(ShoppingHandler::clear(&profiles, user).await?)
+            }
+        }
+        let mut answer = bot.answer_callback_query(callback.id);
+        if let Some(notice) = notice {
+            answer = answer.text(notice);
+        }
+        answer.await?;
+        Ok(())
+    }
+
+    /// A message with a single row of result buttons belongs to one drink only.
+    fn expands_in_place(message: &Message) -> bool {
+        message
+            .reply_markup()
+            .map(|markup| {
+                markup.inline_keyboard.len() == 1
+                    && markup.inline_keyboard[0]
+                        .iter()
+                        .all(|button| match &button.kind {
+                            InlineKeyboardButtonKind::CallbackData(data) => matches!(
+                                CallbackData::try_from(data.as_str()),
+                                Ok(CallbackData::Details(_)
+                                    | CallbackData::Save(_)
+                                    | CallbackData::Shop(_))
+                            ),
+                            _ => false,
+                        })
+            })
+            .unwrap_or(false)
+    }
+
+    pub async fn send_message_with_keyboard(
+        message: &str,
+        keyboard: Option<InlineKeyboardMarkup>,
+        bot: &AutoSend<Bot>,
+        dialogue: &LocalDialogue,
+    ) -> ReturnTy {
+        let keyboard = match keyboard {
+            Some(keyboard) => keyboard,
+            None => return Self::send_message(message, bot, dialogue).await,
+        };
+        let mut parts = match message.len() >= TELEGRAMM_CHAR_LIMIT {
+            true => helpers::split(message),
+            false => vec![message.to_string()],
+        };
+        let last = parts.pop().unwrap_or_default();
+        for part in parts {
+            bot.send_message(dialogue.chat_id(), part).await?;
+        }
+        bot.send_message(dialogue.chat_id(), last)
+            .reply_markup(keyboard)
+            .await?;
         Ok(())
     }
 
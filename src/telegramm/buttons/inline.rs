@@ -0,0 +1,390 @@
+use std::fmt::{Display, Formatter};
+
+use serde::{Deserialize, Serialize};
+use teloxide::types::{InlineKeyboardButton, InlineKeyboardMarkup};
+
+use crate::cocktails_api::schemas::drink::{LangDrink, LangLazyDrink};
+use crate::cocktails_api::schemas::ingredient::LangIngredient;
+use crate::cocktails_api::schemas::matches::LangMatch;
+use crate::cocktails_api::schemas::taste::LangRecommendation;
+use crate::error::error_handler::{ErrorHandler, ErrorType};
+use crate::game::questions::Answer;
+use crate::localization::lang::Lang;
+
+const SEPARATOR: char = ':';
+const CHECK_MARK: char = '\u{2705}';
+/// Telegram rejects the whole keyboard when any button has more data.
+const MAX_CALLBACK_BYTES: usize = 64;
+
+/// Buttons attached to a single result, serialized as `action:argument`.
+/// Telegram limits callback data to 64 bytes, so ids and indexes are passed,
+/// the ingredient names of `BarAdd` are checked with `fits`.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub enum CallbackData {
+    Details(String),
+    /// Index of the ingredient in the popular ones.
+    Toggle(usize),
+    Search,
+    BarAdd(String),
+    Save(String),
+    FavoritesPage(usize),
+    FavoritesRemove(usize, String),
+    Page(usize),
+    Vote(i64, usize),
+    NotForMe(String),
+    /// Locale code from the language picker.
+    Lang(String),
+    Scale(String),
+    Servings(String, u32),
+    ScaleCustom(String),
+    Batch(String),
+    /// Adds the drink to the shopping list.
+    Shop(String),
+    ShopFavorites,
+    ShopSend,
+    ShopFile,
+    ShopClear,
+    /// Answer to the question of the drink-for-you game.
+    Game(Answer),
+}
+
+impl CallbackData {
+    pub fn label(&self, lang: &Lang) -> String {
+        match self {
+            CallbackData::Details(_) => lang.buttons.inline.details.clone(),
+            CallbackData::Toggle(index) => (index + 1).to_string(),
+            CallbackData::Search => lang.buttons.inline.search.clone(),
+            CallbackData::BarAdd(_) => lang.buttons.inline.bar_add.clone(),
+            CallbackData::Save(_) => lang.buttons.inline.save.clone(),
+            CallbackData::FavoritesPage(page) => page.to_string(),
+            CallbackData::FavoritesRemove(..) => lang.buttons.inline.remove.clone(),
+            CallbackData::Page(page) => (page + 1).to_string(),
+            CallbackData::Vote(_, option) => (option + 1).to_string(),
+            CallbackData::NotForMe(_) => lang.buttons.inline.not_for_me.clone(),
+            CallbackData::Lang(code) => Lang::new(code).name.clone(),
+            CallbackData::Scale(_) => lang.scale.button.clone(),
+            CallbackData::Servings(_, servings) => format!("×{}", servings),
+            CallbackData::ScaleCustom(_) => lang.scale.custom.clone(),
+            CallbackData::Batch(_) => lang.scale.batch.clone(),
+            CallbackData::Shop(_) => lang.shopping.add.clone(),
+            CallbackData::ShopFavorites => lang.shopping.favorites.clone(),
+            CallbackData::ShopSend => lang.shopping.message.clone(),
+            CallbackData::ShopFile => lang.shopping.file.clone(),
+            CallbackData::ShopClear => lang.shopping.clear.clone(),
+            CallbackData::Game(answer) => answer.to_string(),
+        }
+    }
+
+    pub fn fits(&self) -> bool {
+        self.to_string().len() <= MAX_CALLBACK_BYTES
+    }
+
+    pub fn button(&self, lang: &Lang) -> InlineKeyboardButton {
+        InlineKeyboardButton::callback(self.label(lang), self.to_string())
+    }
+}
+
+impl Display for CallbackData {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            CallbackData::Details(id) => write!(f, "details{}{}", SEPARATOR, id),
+            CallbackData::Toggle(index) => write!(f, "toggle{}{}", SEPARATOR, index),
+            CallbackData::Search => write!(f, "search{}", SEPARATOR),
+            CallbackData::BarAdd(name) => write!(f, "bar_add{}{}", SEPARATOR, name),
+            CallbackData::Save(id) => write!(f, "save{}{}", SEPARATOR, id),
+            CallbackData::FavoritesPage(page) => write!(f, "fav_page{}{}", SEPARATOR, page),
+            CallbackData::FavoritesRemove(page, id) => {
+                write!(f, "fav_remove{}{}{}{}", SEPARATOR, page, SEPARATOR, id)
+            }
+            CallbackData::Page(page) => write!(f, "page{}{}", SEPARATOR, page),
+            CallbackData::Vote(id, option) => {
+                write!(f, "vote{}{}{}{}", SEPARATOR, id, SEPARATOR, option)
+            }
+            CallbackData::NotForMe(id) => write!(f, "not_for_me{}{}", SEPARATOR, id),
+            CallbackData::Lang(code) => write!(f, "lang{}{}", SEPARATOR, code),
+            CallbackData::Scale(id) => write!(f, "scale{}{}", SEPARATOR, id),
+            CallbackData::Servings(id, servings) => {
+                write!(f, "servings{}{}{}{}", SEPARATOR, id, SEPARATOR, servings)
+            }
+            CallbackData::ScaleCustom(id) => write!(f, "scale_custom{}{}", SEPARATOR, id),
+            CallbackData::Batch(id) => write!(f, "batch{}{}", SEPARATOR, id),
+            CallbackData::Shop(id) => write!(f, "shop{}{}", SEPARATOR, id),
+            CallbackData::ShopFavorites => write!(f, "shop_fav{}", SEPARATOR),
+            CallbackData::ShopSend => write!(f, "shop_send{}", SEPARATOR),
+            CallbackData::ShopFile => write!(f, "shop_file{}", SEPARATOR),
+            CallbackData::ShopClear => write!(f, "shop_clear{}", SEPARATOR),
+            CallbackData::Game(answer) => write!(f, "game{}{}", SEPARATOR, answer),
+        }
+    }
+}
+
+impl TryFrom<&str> for CallbackData {
+    type Error = ErrorHandler;
+
+    fn try_from(value: &str) -> Result<Self, Self::Error> {
+        let error = || ErrorHandler {
+            msg: format!("Wrong callback data: {}", value),
+            ty: ErrorType::Parse,
+        };
+        let (action, argument) = value.split_once(SEPARATOR).ok_or_else(error)?;
+        match action {
+            "details" => Ok(CallbackData::Details(argument.to_string())),
+            "toggle" => Ok(CallbackData::Toggle(
+                argument.parse::<usize>().map_err(|_| error())?,
+            )),
+            "search" => Ok(CallbackData::Search),
+            "bar_add" => Ok(CallbackData::BarAdd(argument.to_string())),
+            "save" => Ok(CallbackData::Save(argument.to_string())),
+            "fav_page" => Ok(CallbackData::FavoritesPage(
+                argument.parse::<usize>().map_err(|_| error())?,
+            )),
+            "fav_remove" => {
+                let (page, id) = argument.split_once(SEPARATOR).ok_or_else(error)?;
+                Ok(CallbackData::FavoritesRemove(
+                    page.parse::<usize>().map_err(|_| error())?,
+                    id.to_string(),
+                ))
+            }
+            "page" => Ok(CallbackData::Page(
+                argument.parse::<usize>().map_err(|_| error())?,
+            )),
+            "vote" => {
+                let (id, option) = argument.split_once(SEPARATOR).ok_or_else(error)?;
+                Ok(CallbackData::Vote(
+                    id.parse::<i64>().map_err(|_| error())?,
+                    option.parse::<usize>().map_err(|_| error())?,
+                ))
+            }
+            "not_for_me" => Ok(CallbackData::NotForMe(argument.to_string())),
+            "lang" => Ok(CallbackData::Lang(argument.to_string())),
+            "scale" => Ok(CallbackData::Scale(argument.to_string())),
+            "servings" => {
+                let (id, servings) = argument.split_once(SEPARATOR).ok_or_else(error)?;
+                Ok(CallbackData::Servings(
+                    id.to_string(),
+                    servings.parse::<u32>().map_err(|_| error())?,
+                ))
+            }
+            "scale_custom" => Ok(CallbackData::ScaleCustom(argument.to_string())),
+            "batch" => Ok(CallbackData::Batch(argument.to_string())),
+            "shop" => Ok(CallbackData::Shop(argument.to_string())),
+            "shop_fav" => Ok(CallbackData::ShopFavorites),
+            "shop_send" => Ok(CallbackData::ShopSend),
+            "shop_file" => Ok(CallbackData::ShopFile),
+            "shop_clear" => Ok(CallbackData::ShopClear),
+            "game" => Ok(CallbackData::Game(
+                Answer::try_from(argument).map_err(|_| error())?,
+            )),
+            _ => Err(error()),
+        }
+    }
+}
+
+/// Inline actions which are available for a result.
+pub trait WithActions {
+    fn title(&self) -> String;
+    fn actions(&self) -> Vec<CallbackData>;
+    /// Drink the result shows, it counts as viewed once sent.
+    fn drink_id(&self) -> Option<String>;
+}
+
+impl WithActions for LangDrink {
+    fn title(&self) -> String {
+        self.drink.name.clone()
+    }
+    fn actions(&self) -> Vec<CallbackData> {
+        match &self.drink.id {
+            Some(id) => vec![
+                CallbackData::Save(id.clone()),
+                CallbackData::Scale(id.clone()),
+                CallbackData::Shop(id.clone()),
+            ],
+            None => vec![],
+        }
+    }
+    fn drink_id(&self) -> Option<String> {
+        self.drink.id.clone()
+    }
+}
+
+impl WithActions for LangLazyDrink {
+    fn title(&self) -> String {
+        self.lazy.name.clone()
+    }
+    fn actions(&self) -> Vec<CallbackData> {
+        vec![
+            CallbackData::Details(self.lazy.id.clone()),
+            CallbackData::Save(self.lazy.id.clone()),
+            CallbackData::Shop(self.lazy.id.clone()),
+        ]
+    }
+    fn drink_id(&self) -> Option<String> {
+        Some(self.lazy.id.clone())
+    }
+}
+
+impl WithActions for LangRecommendation {
+    fn title(&self) -> String {
+        self.recommendation.drink.name.clone()
+    }
+    fn actions(&self) -> Vec<CallbackData> {
+        let mut actions = self.as_lang_drink().actions();
+        if let Some(id) = &self.recommendation.drink.id {
+            actions.push(CallbackData::NotForMe(id.clone()));
+        }
+        actions
+    }
+    fn drink_id(&self) -> Option<String> {
+        self.recommendation.drink.id.clone()
+    }
+}
+
+impl WithActions for LangIngredient {
+    fn title(&self) -> String {
+        self.ingredient.name.clone()
+    }
+    fn actions(&self) -> Vec<CallbackData> {
+        vec![CallbackData::BarAdd(self.ingredient.name.clone())]
+    }
+    fn drink_id(&self) -> Option<String> {
+        None
+    }
+}
+
+impl WithActions for LangMatch {
+    fn title(&self) -> String {
+        self.matched.drink.name.clone()
+    }
+    fn actions(&self) -> Vec<CallbackData> {
+        self.as_lang_drink().actions()
+    }
+    fn drink_id(&self) -> Option<String> {
+        self.matched.drink.id.clone()
+    }
+}
+
+/// One row of buttons per result, labels are prefixed with the result title
+/// when the keyboard is shared by several results.
+pub fn actions_keyboard<T: WithActions>(
+    results: &[T],
+    lang: &Lang,
+) -> Option<InlineKeyboardMarkup> {
+    let keyboard = results
+        .iter()
+        .map(|result| {
+            result
+                .actions()
+                .iter()
+                .filter(|action| action.fits())
+                .map(|action| match results.len() {
+                    1 => action.button(lang),
+                    _ => InlineKeyboardButton::callback(
+                        format!("{} {}", action.label(lang), result.title()),
+                        action.to_string(),
+                    ),
+                })
+                .collect::<Vec<InlineKeyboardButton>>()
+        })
+        .filter(|row| !row.is_empty())
+        .collect::<Vec<Vec<InlineKeyboardButton>>>();
+    match keyboard.is_empty() {
+        true => None,
+        false => Some(InlineKeyboardMarkup::new(keyboard)),
+    }
+}
+
+/// Toggle buttons for ingredients, the picked ones are marked.
+pub fn ingredients_keyboard(
+    options: &[String],
+    selected: &[String],
+    lang: &Lang,
+) -> InlineKeyboardMarkup {
+    let options = options
+        .iter()
+        .enumerate()
+        .collect::<Vec<(usize, &String)>>();
+    let mut keyboard = options
+        .chunks(2)
+        .map(|chunk| {
+            chunk
+                .iter()
+                .map(|(index, name)| {
+                    let label = match selected.contains(name) {
+                        true => format!("{} {}", CHECK_MARK, lang.names.ingredient(name)),
+                        false => lang.names.ingredient(name),
+                    };
+                    InlineKeyboardButton::callback(label, CallbackData::Toggle(*index).to_string())
+                })
+                .collect::<Vec<InlineKeyboardButton>>()
+        })
+        .collect::<Vec<Vec<InlineKeyboardButton>>>();
+    keyboard.push(vec![CallbackData::Search.button(lang)]);
+    InlineKeyboardMarkup::new(keyboard)
+}
+
+#[cfg(test)]
+mod test {
+    use crate::game::questions::Answer;
+    use crate::telegramm::buttons::inline::CallbackData;
+
+    #[test]
+    fn test_round_trip() {
+        let data = CallbackData::Details("11007".to_string());
+        assert_eq!(
+            CallbackData::try_from(data.to_string().as_str()).unwrap(),
+            data
+        );
+        assert_eq!(
+            CallbackData::try_from(CallbackData::Search.to_string().as_str()).unwrap(),
+            CallbackData::Search
+        );
+        let data = CallbackData::FavoritesRemove(2, "11007".to_string());
+        assert_eq!(
+            CallbackData::try_from(data.to_string().as_str()).unwrap(),
+            data
+        );
+        assert_eq!(
+            CallbackData::try_from(CallbackData::Page(3).to_string().as_str()).unwrap(),
+            CallbackData::Page(3)
+        );
+        let data = CallbackData::Vote(12, 3);
+        assert_eq!(
+            CallbackData::try_from(data.to_string().as_str()).unwrap(),
+            data
+        );
+        let data = CallbackData::NotForMe("11007".to_string());
+        assert_eq!(
+            CallbackData::try_from(data.to_string().as_str()).unwrap(),
+            data
+        );
+        let data = CallbackData::Lang("uk".to_string());
+        assert_eq!(
+            CallbackData::try_from(data.to_string().as_str()).unwrap(),
+            data
+        );
+        let data = CallbackData::Servings("11007".to_string(), 4);
+        assert_eq!(
+            CallbackData::try_from(data.to_string().as_str()).unwrap(),
+            data
+        );
+        assert_eq!(
+            CallbackData::try_from(CallbackData::ShopFile.to_string().as_str()).unwrap(),
+            CallbackData::ShopFile
+        );
+        assert_eq!(
+            CallbackData::try_from("game:either").unwrap(),
+            CallbackData::Game(Answer::Either)
+        );
+        assert!(CallbackData::try_from("game:Vodka").is_err());
+        assert!(CallbackData::try_from("0").is_err());
+        assert_eq!(
+            CallbackData::try_from("toggle:3").unwrap(),
+            CallbackData::Toggle(3)
+        );
+        assert!(CallbackData::BarAdd("Vodka".to_string()).fits());
+        assert!(!CallbackData::BarAdd("Лимонний сік".repeat(3)).fits());
+        assert!(CallbackData::try_from("servings:11007").is_err());
+        assert!(CallbackData::try_from("fav_page:next").is_err());
+        assert!(CallbackData::try_from("FindCocktail").is_err());
+    }
+}
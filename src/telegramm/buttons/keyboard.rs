@@ -14,6 +14,8 @@ pub enum Keyboard {
     WithThisIngredient,
     WithThisCategory,
     DrinkForYou,
+    WhatCanIMake,
+    Recommended,
     Settings,
 }
 
@@ -1,2 +1,3 @@
 pub mod callback_handler;
+pub mod inline;
 pub mod keyboard;
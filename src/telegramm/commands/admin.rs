@@ -0,0 +1,321 @@
+use std::sync::Arc;
+use std::time::Duration;
+
+use teloxide::prelude::{AutoSend, Message, Requester, Update};
+use teloxide::types::{ChatId, InputFile, UpdateKind};
+use teloxide::Bot;
+
+use crate::cocktails_api::services::cached_source::CachedSource;
+use crate::error::error_handler::ErrorHandler;
+use crate::localization;
+use crate::localization::lang::Lang;
+use crate::storage;
+use crate::storage::bans::BanStore;
+use crate::storage::feedback::{Feedback, FeedbackStatus, FeedbackStore};
+use crate::storage::profiles::ProfileStore;
+use crate::storage::stats::StatsStore;
+use crate::telegramm::buttons::inline::CallbackData;
+use crate::telegramm::commands::command::AdminCommands;
+use crate::telegramm::settings::settings::UserSettings;
+use crate::telegramm::ReturnTy;
+
+/// Telegram allows about 30 messages per second to different chats.
+const BROADCAST_DELAY: Duration = Duration::from_millis(50);
+const FEEDBACK_PAGE: usize = 10;
+
+/// User ids from `Env::admin_ids`.
+pub struct Admins(pub Vec<u64>);
+
+impl Admins {
+    pub fn is_admin(&self, message: &Message) -> bool {
+        message
+            .from()
+            .map(|user| self.0.contains(&user.id.0))
+            .unwrap_or(false)
+    }
+}
+
+pub struct AdminHandler;
+
+impl AdminHandler {
+    #[allow(clippy::too_many_arguments)]
+    pub async fn handle_commands(
+        bot: AutoSend<Bot>,
+        message: Message,
+        command: AdminCommands,
+        profiles: Arc<ProfileStore>,
+        stats: Arc<StatsStore>,
+        bans: Arc<BanStore>,
+        feedback: Arc<FeedbackStore>,
+        cache: Arc<CachedSource>,
+    ) -> ReturnTy {
+        let chat_id = message.chat.id;
+        let UserSettings { lang, .. } = profiles.get(chat_id).await?;
+        let admin = &lang.admin;
+        let text = match command {
+            AdminCommands::Stats => {
+                let stats = stats.stats(storage::now()).await?;
+                let features = stats
+                    .features
+                    .iter()
+                    .map(|(feature, requests)| format!(" - {}: {}", feature, requests))
+                    .collect::<Vec<String>>()
+                    .join("\n");
+                format!(
+                    "{} {}\n{} {}\n{} {}\n{}\n{}\n{}\n{} {}",
+                    admin.chats,
+                    stats.chats,
+                    admin.profiles,
+                    stats.profiles,
+                    admin.active,
+                    stats.active_chats,
+                    admin.requests,
+                    features,
+                    cache.stats(),
+                    admin.persisted,
+                    cache.persisted_size().await?
+                )
+            }
+            AdminCommands::Broadcast(text) if text.trim().is_empty() => admin.need_text.clone(),
+            AdminCommands::Broadcast(text) => {
+                tokio::spawn(Self::broadcast(
+                    bot.clone(),
+                    chat_id,
+                    lang.clone(),
+                    text,
+                    stats,
+                    bans,
+                ));
+                admin.broadcast_started.clone()
+            }
+            AdminCommands::Suggestions(status) => {
+                let status = match status.trim() {
+                    "" => Some(FeedbackStatus::New),
+                    "all" => None,
+                    status => Some(FeedbackStatus::try_from(status)?),
+                };
+                let list = feedback.list(status, FEEDBACK_PAGE).await?;
+                match list.is_empty() {
+                    true => admin.no_suggestions.clone(),
+                    false => list
+                        .iter()
+                        .map(Self::describe)
+                        .collect::<Vec<String>>()
+                        .join("\n\n"),
+                }
+            }
+            AdminCommands::Feedback(id) => match Self::find(&feedback, &id).await? {
+                Some(found) => {
+                    if let Some(photo) = &found.photo {
+                        // Screenshots sent as files are kept as documents.
+                        if bot
+                            .send_photo(chat_id, InputFile::file_id(photo))
+                            .await
+                            .is_err()
+                        {
+                            bot.send_document(chat_id, InputFile::file_id(photo))
+                                .await?;
+                        }
+                    }
+                    Self::describe(&found)
+                }
+                None => admin.feedback_not_found.clone(),
+            },
+            AdminCommands::Ack(id) => {
+                Self::set_status(&feedback, &id, FeedbackStatus::Acknowledged, &lang).await?
+            }
+            AdminCommands::Resolve(id) => {
+                Self::set_status(&feedback, &id, FeedbackStatus::Resolved, &lang).await?
+            }
+            AdminCommands::Reply(reply) => {
+                let (id, text) = reply.trim().split_once(' ').unwrap_or((reply.trim(), ""));
+                match (Self::find(&feedback, id).await?, text.trim()) {
+                    (_, "") => admin.need_reply.clone(),
+                    (Some(found), _) if found.chat_id == 0 => {
+                        format!("#{} {}", found.id, admin.author_unknown)
+                    }
+                    (Some(found), text) => {
+                        let author = ChatId(found.chat_id);
+                        let UserSettings {
+                            lang: author_lang, ..
+                        } = profiles.get(author).await?;
+                        bot.send_message(
+                            author,
+                            format!("{}\n\n{}", author_lang.admin.reply_title, text),
+                        )
+                        .await?;
+                        if found.status == FeedbackStatus::New {
+                            feedback
+                                .set_status(found.id, FeedbackStatus::Acknowledged)
+                                .await?;
+                        }
+                        format!("{} #{}", admin.replied, found.id)
+                    }
+                    (None, _) => admin.feedback_not_found.clone(),
+                }
+            }
+            AdminCommands::ReloadLocales => match localization::reload() {
+                Ok(()) => admin.reloaded.clone(),
+                Err(error) => format!("{} {}", admin.reload_failed, error.msg),
+            },
+            AdminCommands::Ban(id) => match id.trim().parse::<i64>() {
+                Ok(id) => match bans.ban(id).await? {
+                    true => format!("{} {}", admin.banned, id),
+                    false => format!("{} {}", admin.already_banned, id),
+                },
+                Err(_) => admin.need_id.clone(),
+            },
+            AdminCommands::Unban(id) => match id.trim().parse::<i64>() {
+                Ok(id) => match bans.unban(id).await? {
+                    true => format!("{} {}", admin.unbanned, id),
+                    false => format!("{} {}", admin.not_banned, id),
+                },
+                Err(_) => admin.need_id.clone(),
+            },
+        };
+        bot.send_message(chat_id, text).await?;
+        Ok(())
+    }
+
+    fn describe(feedback: &Feedback) -> String {
+        let author = match &feedback.username {
+            Some(username) => format!("@{}", username),
+            None => feedback.user_id.to_string(),
+        };
+        format!(
+            "#{} [{}] {} ({}){}\n{}",
+            feedback.id,
+            feedback.status.as_str(),
+            author,
+            feedback.lang,
+            if feedback.photo.is_some() {
+                " \u{1F4F7}"
+            } else {
+                ""
+            },
+            feedback.text
+        )
+    }
+
+    async fn find(feedback: &FeedbackStore, id: &str) -> Result<Option<Feedback>, ErrorHandler> {
+        match id.trim().trim_start_matches('#').parse::<i64>() {
+            Ok(id) => feedback.get(id).await,
+            Err(_) => Ok(None),
+        }
+    }
+
+    async fn set_status(
+        feedback: &FeedbackStore,
+        id: &str,
+        status: FeedbackStatus,
+        lang: &Lang,
+    ) -> Result<String, ErrorHandler> {
+        let found = match Self::find(feedback, id).await? {
+            Some(found) => found,
+            None => return Ok(lang.admin.feedback_not_found.clone()),
+        };
+        feedback.set_status(found.id, status).await?;
+        Ok(format!("#{} {}", found.id, status.as_str()))
+    }
+
+    /// Banned users are ignored, everything else is counted per feature.
+    pub fn allow(update: Update, bans: Arc<BanStore>) -> bool {
+        !update
+            .user()
+            .map(|user| bans.is_banned(user.id.0 as i64))
+            .unwrap_or(false)
+    }
+
+    pub fn count(update: Update, stats: Arc<StatsStore>) -> bool {
+        let chat_id = update
+            .chat()
+            .map(|chat| chat.id.0)
+            .or_else(|| update.user().map(|user| user.id.0 as i64));
+        stats.hit(&Self::feature(&update), chat_id, storage::now());
+        true
+    }
+
+    async fn broadcast(
+        bot: AutoSend<Bot>,
+        admin: ChatId,
+        lang: Lang,
+        text: String,
+        stats: Arc<StatsStore>,
+        bans: Arc<BanStore>,
+    ) {
+        let chats = match stats.chats().await {
+            Ok(chats) => chats,
+            Err(error) => {
+                log::error!("Fail to load chats for the broadcast: {:?}", error);
+                return;
+            }
+        };
+        let mut sent = 0;
+        let mut interval = tokio::time::interval(BROADCAST_DELAY);
+        for chat in chats.iter() {
+            if bans.is_banned(*chat) {
+                continue;
+            }
+            interval.tick().await;
+            match bot.send_message(ChatId(*chat), &text).await {
+                Ok(_) => sent += 1,
+                Err(error) => log::warn!("Fail to broadcast to {}: {:?}", chat, error),
+            }
+        }
+        let report = format!("{} {}/{}", lang.admin.broadcast_done, sent, chats.len());
+        if let Err(error) = bot.send_message(admin, report).await {
+            log::error!("Fail to report the broadcast: {:?}", error);
+        }
+    }
+
+    fn feature(update: &Update) -> String {
+        match &update.kind {
+            UpdateKind::Message(message) => match message.text() {
+                Some(text) if text.starts_with('/') => text
+                    .split(|symbol: char| symbol.is_whitespace() || symbol == '@')
+                    .next()
+                    .unwrap_or_default()
+                    .to_lowercase(),
+                _ => "message".to_string(),
+            },
+            UpdateKind::CallbackQuery(callback) => match callback.data.as_deref() {
+                Some(data) => Self::callback_feature(data),
+                None => "callback".to_string(),
+            },
+            UpdateKind::InlineQuery(_) => "inline_query".to_string(),
+            _ => "other".to_string(),
+        }
+    }
+
+    /// Inline actions by their name, main keyboard buttons by their key in any language.
+    fn callback_feature(data: &str) -> String {
+        if CallbackData::try_from(data).is_ok() {
+            return data.split(':').next().unwrap_or_default().to_string();
+        }
+        Lang::available()
+            .iter()
+            .find_map(|lang| {
+                lang.buttons
+                    .main
+                    .iter()
+                    .find(|(_, label)| label.as_str() == data)
+                    .map(|(key, _)| key.clone())
+            })
+            .unwrap_or_else(|| "answer".to_string())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::telegramm::commands::admin::AdminHandler;
+
+    #[test]
+    fn test_callback_feature() {
+        assert_eq!(AdminHandler::callback_feature("details:11007"), "details");
+        assert_eq!(
+            AdminHandler::callback_feature("Drink for you"),
+            "DrinkForYou"
+        );
+        assert_eq!(AdminHandler::callback_feature("2"), "answer");
+    }
+}
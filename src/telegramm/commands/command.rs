@@ -1,11 +1,39 @@
-use macroses::as_array;
 use teloxide::utils::command::BotCommands;
 
-#[derive(as_array, Clone, BotCommands, Eq, PartialEq, Debug)]
+#[derive(Clone, BotCommands, Eq, PartialEq, Debug)]
 #[command(rename = "lowercase")]
 pub enum StartCommands {
     Back,
     Random,
     #[command(rename = "suggestion")]
     SuggestionAndBags,
+    Bar,
+    #[command(rename = "bar_add")]
+    BarAdd(String),
+    #[command(rename = "bar_remove")]
+    BarRemove(String),
+    Favorites,
+    Shopping,
+    Vote,
+    Subscribe(String),
+    Unsubscribe,
+}
+
+// Only for the users listed in `Env::admin_ids`.
+#[derive(Clone, BotCommands, Eq, PartialEq, Debug)]
+#[command(rename = "lowercase")]
+pub enum AdminCommands {
+    Stats,
+    Broadcast(String),
+    // `new` by default, `acknowledged`, `resolved` or `all`.
+    Suggestions(String),
+    Feedback(String),
+    Ack(String),
+    Resolve(String),
+    // `/reply <id> <text>` to the author of the feedback.
+    Reply(String),
+    #[command(rename = "reload_locales")]
+    ReloadLocales,
+    Ban(String),
+    Unban(String),
 }
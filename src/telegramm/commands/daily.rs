@@ -0,0 +1,212 @@
+use std::sync::Arc;
+use std::time::Duration;
+
+use teloxide::prelude::{AutoSend, Message, Requester};
+use teloxide::types::{ChatId, InputFile};
+use teloxide::Bot;
+use url::Url;
+
+use crate::cocktails_api::services::coctail_service::DrinksService;
+use crate::error::error_handler::ErrorHandler;
+use crate::storage;
+use crate::storage::history::{Event, HistoryStore};
+use crate::storage::profiles::ProfileStore;
+use crate::storage::subscriptions::SubscriptionStore;
+use crate::telegramm::commands::func::CommandsHandler;
+use crate::telegramm::messages::group::GroupHandler;
+use crate::telegramm::settings::settings::UserSettings;
+use crate::telegramm::{LocalDialogue, ReturnTy};
+use crate::utils::helpers::random_num_in_range;
+
+const WATCH_INTERVAL: u64 = 30;
+
+pub struct DailyHandler;
+
+impl DailyHandler {
+    /// `/subscribe 18:30 +2`, the offset from UTC is optional.
+    pub async fn subscribe(
+        bot: &AutoSend<Bot>,
+        dialogue: &LocalDialogue,
+        message: &Message,
+        profiles: &ProfileStore,
+        subscriptions: &SubscriptionStore,
+        schedule: &str,
+    ) -> ReturnTy {
+        let UserSettings { lang, .. } = profiles.get(dialogue.chat_id()).await?;
+        if !Self::allowed(bot, dialogue, message).await? {
+            bot.send_message(dialogue.chat_id(), &lang.fail_messages.admins_only)
+                .await?;
+            return Ok(());
+        }
+        let (minute, offset) = match Self::parse(schedule) {
+            Some(parsed) => parsed,
+            None => {
+                bot.send_message(dialogue.chat_id(), &lang.daily.format)
+                    .await?;
+                return Ok(());
+            }
+        };
+        subscriptions
+            .subscribe(dialogue.chat_id(), minute, offset, storage::now())
+            .await?;
+        bot.send_message(
+            dialogue.chat_id(),
+            format!(
+                "{} {:02}:{:02} (UTC{}{:02}:{:02})",
+                lang.daily.subscribed,
+                minute / 60,
+                minute % 60,
+                if offset < 0 { '-' } else { '+' },
+                offset.abs() / 60,
+                offset.abs() % 60
+            ),
+        )
+        .await?;
+        Ok(())
+    }
+
+    pub async fn unsubscribe(
+        bot: &AutoSend<Bot>,
+        dialogue: &LocalDialogue,
+        message: &Message,
+        profiles: &ProfileStore,
+        subscriptions: &SubscriptionStore,
+    ) -> ReturnTy {
+        let UserSettings { lang, .. } = profiles.get(dialogue.chat_id()).await?;
+        if !Self::allowed(bot, dialogue, message).await? {
+            bot.send_message(dialogue.chat_id(), &lang.fail_messages.admins_only)
+                .await?;
+            return Ok(());
+        }
+        let text = match subscriptions.unsubscribe(dialogue.chat_id()).await? {
+            true => &lang.daily.unsubscribed,
+            false => &lang.daily.not_subscribed,
+        };
+        bot.send_message(dialogue.chat_id(), text).await?;
+        Ok(())
+    }
+
+    /// Sends the drinks which are due, the ones missed during a restart included.
+    pub async fn watch(
+        bot: AutoSend<Bot>,
+        service: Arc<DrinksService>,
+        profiles: Arc<ProfileStore>,
+        subscriptions: Arc<SubscriptionStore>,
+        history: Arc<HistoryStore>,
+    ) {
+        let mut interval = tokio::time::interval(Duration::from_secs(WATCH_INTERVAL));
+        loop {
+            interval.tick().await;
+            let due = match subscriptions.due(storage::now()).await {
+                Ok(due) => due,
+                Err(error) => {
+                    log::error!("Fail to load subscriptions: {:?}", error);
+                    continue;
+                }
+            };
+            for (subscription, day) in due {
+                let chat_id = ChatId(subscription.chat_id);
+                // Marked first, a chat which blocked the bot is not retried all day long.
+                if let Err(error) = subscriptions.mark_sent(chat_id, day).await {
+                    log::error!("Fail to mark the drink of the day: {:?}", error);
+                    continue;
+                }
+                if let Err(error) = Self::send(&bot, &service, &profiles, &history, chat_id).await {
+                    log::error!("Fail to send the drink of the day: {:?}", error);
+                }
+            }
+        }
+    }
+
+    async fn send(
+        bot: &AutoSend<Bot>,
+        service: &DrinksService,
+        profiles: &ProfileStore,
+        history: &HistoryStore,
+        chat_id: ChatId,
+    ) -> ReturnTy {
+        let UserSettings { lang, units, .. } = profiles.get(chat_id).await?;
+        let mut drinks = CommandsHandler::till_get(lang.clone(), units, service).await?;
+        let drink = drinks.remove(random_num_in_range(0, drinks.len()));
+        bot.send_message(chat_id, format!("{}\n\n{}", lang.daily.title, drink))
+            .await?;
+        if let Some(image) = &drink.drink.image {
+            bot.send_photo(chat_id, InputFile::url(Url::parse(image)?))
+                .await?;
+        }
+        if let Some(id) = &drink.drink.id {
+            history.record(chat_id, id, Event::Viewed).await?;
+        }
+        Ok(())
+    }
+
+    async fn allowed(
+        bot: &AutoSend<Bot>,
+        dialogue: &LocalDialogue,
+        message: &Message,
+    ) -> Result<bool, ErrorHandler> {
+        match message.from() {
+            Some(user) => GroupHandler::can_configure(bot, dialogue.chat_id(), user).await,
+            None => Ok(false),
+        }
+    }
+
+    /// Minutes after midnight and minutes from UTC, from `HH:MM [+H[:MM]]`.
+    fn parse(schedule: &str) -> Option<(i64, i64)> {
+        let mut parts = schedule.split_whitespace();
+        let minute = Self::parse_time(parts.next()?)?;
+        if minute >= 24 * 60 {
+            return None;
+        }
+        let offset = match parts.next() {
+            Some(offset) => {
+                let offset = offset.trim_start_matches("UTC").trim_start_matches("utc");
+                let (sign, offset) = match offset.strip_prefix('-') {
+                    Some(offset) => (-1, offset),
+                    None => (1, offset.strip_prefix('+').unwrap_or(offset)),
+                };
+                let offset = match offset.contains(':') {
+                    true => Self::parse_time(offset)?,
+                    false => offset.parse::<i64>().ok()? * 60,
+                };
+                if offset > 14 * 60 {
+                    return None;
+                }
+                sign * offset
+            }
+            None => 0,
+        };
+        match parts.next() {
+            Some(_) => None,
+            None => Some((minute, offset)),
+        }
+    }
+
+    fn parse_time(time: &str) -> Option<i64> {
+        let (hours, minutes) = time.split_once(':')?;
+        let hours = hours.parse::<i64>().ok()?;
+        let minutes = minutes.parse::<i64>().ok()?;
+        match (0..60).contains(&minutes) && hours >= 0 {
+            true => Some(hours * 60 + minutes),
+            false => None,
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::telegramm::commands::daily::DailyHandler;
+
+    #[test]
+    fn test_parse() {
+        assert_eq!(DailyHandler::parse("18:30"), Some((18 * 60 + 30, 0)));
+        assert_eq!(DailyHandler::parse("8:05 +3"), Some((8 * 60 + 5, 180)));
+        assert_eq!(DailyHandler::parse("08:00 UTC-5"), Some((480, -300)));
+        assert_eq!(DailyHandler::parse("08:00 +5:30"), Some((480, 330)));
+        assert_eq!(DailyHandler::parse("24:00"), None);
+        assert_eq!(DailyHandler::parse("8:60"), None);
+        assert_eq!(DailyHandler::parse("08:00 +20"), None);
+        assert_eq!(DailyHandler::parse("morning"), None);
+        assert_eq!(DailyHandler::parse(""), None);
+    }
+}
@@ -0,0 +1,162 @@
+use teloxide::payloads::{EditMessageTextSetters, SendMessageSetters};
+use teloxide::prelude::{AutoSend, Message, Requester};
+use teloxide::types::{ChatId, InlineKeyboardButton, InlineKeyboardMarkup};
+use teloxide::Bot;
+
+use crate::cocktails_api::services::coctail_service::DrinksService;
+use crate::error::error_handler::ErrorHandler;
+use crate::storage::profiles::ProfileStore;
+use crate::telegramm::buttons::inline::CallbackData;
+use crate::telegramm::settings::settings::{Favorite, UserSettings};
+use crate::telegramm::{LocalDialogue, ReturnTy};
+
+const PAGE_SIZE: usize = 5;
+
+pub struct FavoritesHandler;
+
+impl FavoritesHandler {
+    pub async fn favorites(
+        bot: &AutoSend<Bot>,
+        dialogue: &LocalDialogue,
+        profiles: &ProfileStore,
+        user: ChatId,
+    ) -> ReturnTy {
+        let settings = profiles.get(user).await?;
+        if settings.favorites.is_empty() {
+            bot.send_message(dialogue.chat_id(), &settings.lang.favorites.empty)
+                .await?;
+            return Ok(());
+        }
+        let (text, keyboard) = Self::page(&settings, 0);
+        bot.send_message(dialogue.chat_id(), text)
+            .reply_markup(keyboard)
+            .await?;
+        Ok(())
+    }
+
+    /// Favourites belong to the user, not to the chat. Returns the notice for the user.
+    pub async fn save(
+        service: &DrinksService,
+        profiles: &ProfileStore,
+        user: ChatId,
+        id: &str,
+    ) -> Result<String, ErrorHandler> {
+        let mut settings = profiles.get(user).await?;
+        if settings.favorites.iter().any(|favorite| favorite.id == id) {
+            return Ok(settings.lang.favorites.already.clone());
+        }
+        let drink = service
+            .lookup_by_id(id, settings.lang.clone(), settings.units)
+            .await?;
+        let notice = match drink {
+            Some(drink) => {
+                settings.favorites.push(Favorite {
+                    id: id.to_string(),
+                    name: drink.drink.name,
+                });
+                settings.lang.favorites.saved.clone()
+            }
+            None => return Ok(settings.lang.fail_messages.non_results.clone()),
+        };
+        profiles.save(user, &settings).await?;
+        Ok(notice)
+    }
+
+    pub async fn remove(
+        bot: &AutoSend<Bot>,
+        dialogue: &LocalDialogue,
+        profiles: &ProfileStore,
+        user: ChatId,
+        message: Option<Message>,
+        page: usize,
+        id: &str,
+    ) -> Result<String, ErrorHandler> {
+        let mut settings = profiles.get(user).await?;
+        settings.favorites.retain(|favorite| favorite.id != id);
+        let notice = settings.lang.favorites.removed.clone();
+        profiles.save(user, &settings).await?;
+        Self::show_page(bot, dialogue, profiles, user, message, page).await?;
+        Ok(notice)
+    }
+
+    /// Edits the favourites message in place.
+    pub async fn show_page(
+        bot: &AutoSend<Bot>,
+        dialogue: &LocalDialogue,
+        profiles: &ProfileStore,
+        user: ChatId,
+        message: Option<Message>,
+        page: usize,
+    ) -> ReturnTy {
+        let settings = profiles.get(user).await?;
+        if let Some(message) = message {
+            if settings.favorites.is_empty() {
+                bot.edit_message_text(
+                    dialogue.chat_id(),
+                    message.id,
+                    &settings.lang.favorites.empty,
+                )
+                .await?;
+            } else {
+                let (text, keyboard) = Self::page(&settings, page);
+                bot.edit_message_text(dialogue.chat_id(), message.id, text)
+                    .reply_markup(keyboard)
+                    .await?;
+            }
+        }
+        Ok(())
+    }
+
+    fn page(settings: &UserSettings, page: usize) -> (String, InlineKeyboardMarkup) {
+        let pages = settings.favorites.len().div_ceil(PAGE_SIZE);
+        let page = page.min(pages.saturating_sub(1));
+        let favorites = settings
+            .favorites
+            .iter()
+            .skip(page * PAGE_SIZE)
+            .take(PAGE_SIZE)
+            .collect::<Vec<&Favorite>>();
+
+        let text = format!(
+            "{} {}/{}\n - {}",
+            settings.lang.favorites.title,
+            page + 1,
+            pages,
+            favorites
+                .iter()
+                .map(|favorite| favorite.name.as_str())
+                .collect::<Vec<&str>>()
+                .join("\n - ")
+        );
+        let mut keyboard = favorites
+            .iter()
+            .map(|favorite| {
+                vec![
+                    InlineKeyboardButton::callback(
+                        favorite.name.clone(),
+                        CallbackData::Details(favorite.id.clone()).to_string(),
+                    ),
+                    CallbackData::FavoritesRemove(page, favorite.id.clone()).button(&settings.lang),
+                ]
+            })
+            .collect::<Vec<Vec<InlineKeyboardButton>>>();
+        let mut navigation = vec![];
+        if page > 0 {
+            navigation.push(InlineKeyboardButton::callback(
+                settings.lang.buttons.inline.prev.clone(),
+                CallbackData::FavoritesPage(page - 1).to_string(),
+            ));
+        }
+        if page + 1 < pages {
+            navigation.push(InlineKeyboardButton::callback(
+                settings.lang.buttons.inline.next.clone(),
+                CallbackData::FavoritesPage(page + 1).to_string(),
+            ));
+        }
+        if !navigation.is_empty() {
+            keyboard.push(navigation);
+        }
+        keyboard.push(vec![CallbackData::ShopFavorites.button(&settings.lang)]);
+        (text, InlineKeyboardMarkup::new(keyboard))
+    }
+}
@@ -1,77 +1,243 @@
+use std::sync::Arc;
+
 use teloxide::prelude::*;
+use teloxide::types::ChatId;
 use teloxide::Bot;
 
 use crate::cocktails_api::schemas::drink::LangDrink;
+use crate::cocktails_api::schemas::measure::Units;
 use crate::cocktails_api::services::coctail_service::DrinksService;
-use crate::error::error_handler::ErrorHandler;
+use crate::error::error_handler::{ErrorHandler, ErrorType};
+use crate::localization::lang::Lang;
+use crate::storage::history::HistoryStore;
+use crate::storage::profiles::ProfileStore;
+use crate::storage::subscriptions::SubscriptionStore;
+use crate::storage::votes::VoteStore;
 use crate::telegramm::buttons::keyboard::{make_keyboard, standard_keyboard_as_str_vec};
+use crate::telegramm::commands::daily::DailyHandler;
+use crate::telegramm::commands::favorites::FavoritesHandler;
+use crate::telegramm::commands::shopping::ShoppingHandler;
+use crate::telegramm::commands::vote::VoteHandler;
+use crate::telegramm::messages::group::GroupHandler;
 use crate::telegramm::settings::settings::UserSettings;
 use crate::telegramm::state::State;
 use crate::telegramm::{LocalDialogue, ReturnTy};
 use crate::utils::helpers::{random_english_character, random_num_in_range};
 use crate::{MessageHandler, StartCommands};
 
+const LETTER_ATTEMPTS: usize = 5;
+
 pub struct CommandsHandler;
 
 impl CommandsHandler {
-    pub async fn start_commands(bot: &AutoSend<Bot>, dialogue: &LocalDialogue) -> ReturnTy {
-        let UserSettings { lang, .. } = CommandsHandler::get_settings(dialogue).await?;
+    pub async fn start_commands(
+        bot: &AutoSend<Bot>,
+        dialogue: &LocalDialogue,
+        profiles: &ProfileStore,
+    ) -> ReturnTy {
+        let UserSettings { lang, .. } = profiles.get(dialogue.chat_id()).await?;
         let keyboard = make_keyboard(&standard_keyboard_as_str_vec(&lang));
-        dialogue
-            .update(State::CallBack(
-                CommandsHandler::get_settings(dialogue).await?,
-            ))
-            .await?;
+        dialogue.update(State::CallBack).await?;
         bot.send_message(dialogue.chat_id(), &lang.send_commands)
             .reply_markup(keyboard)
             .await?;
 
         Ok(())
     }
+    #[allow(clippy::too_many_arguments)]
     pub async fn handle_commands(
         bot: AutoSend<Bot>,
         dialogue: LocalDialogue,
+        message: Message,
         command: StartCommands,
+        service: Arc<DrinksService>,
+        profiles: Arc<ProfileStore>,
+        votes: Arc<VoteStore>,
+        subscriptions: Arc<SubscriptionStore>,
+        history: Arc<HistoryStore>,
     ) -> ReturnTy {
+        let user = GroupHandler::message_user_chat(&message);
         match command {
-            StartCommands::Back => Self::start_commands(&bot, &dialogue).await?,
-            StartCommands::Random => Self::random(&bot, &dialogue).await?,
-            StartCommands::SuggestionAndBags => Self::suggestion_bugs(&bot, &dialogue).await?,
+            StartCommands::Back => Self::start_commands(&bot, &dialogue, &profiles).await?,
+            StartCommands::Random => {
+                Self::random(&bot, &dialogue, &service, &profiles, &history, user).await?
+            }
+            StartCommands::SuggestionAndBags => {
+                Self::suggestion_bugs(&bot, &dialogue, &profiles).await?
+            }
+            StartCommands::Bar => Self::bar(&bot, &dialogue, &profiles, user).await?,
+            StartCommands::BarAdd(names) => {
+                Self::bar_add(&bot, &dialogue, &profiles, user, &names).await?
+            }
+            StartCommands::BarRemove(names) => {
+                Self::bar_remove(&bot, &dialogue, &profiles, user, &names).await?
+            }
+            StartCommands::Favorites => {
+                FavoritesHandler::favorites(&bot, &dialogue, &profiles, user).await?
+            }
+            StartCommands::Shopping => {
+                ShoppingHandler::shopping(&bot, &dialogue, &profiles, user).await?
+            }
+            StartCommands::Vote => {
+                VoteHandler::start(&bot, &dialogue, &service, &profiles, &votes).await?
+            }
+            StartCommands::Subscribe(schedule) => {
+                DailyHandler::subscribe(
+                    &bot,
+                    &dialogue,
+                    &message,
+                    &profiles,
+                    &subscriptions,
+                    &schedule,
+                )
+                .await?
+            }
+            StartCommands::Unsubscribe => {
+                DailyHandler::unsubscribe(&bot, &dialogue, &message, &profiles, &subscriptions)
+                    .await?
+            }
         };
         Ok(())
     }
-    async fn suggestion_bugs(bot: &AutoSend<Bot>, dialogue: &LocalDialogue) -> ReturnTy {
-        let settings = CommandsHandler::get_settings(dialogue).await?;
+    async fn bar(
+        bot: &AutoSend<Bot>,
+        dialogue: &LocalDialogue,
+        profiles: &ProfileStore,
+        user: ChatId,
+    ) -> ReturnTy {
+        let settings = profiles.get(user).await?;
+        let message = match settings.bar.is_empty() {
+            true => settings.lang.bar.empty.clone(),
+            false => format!(
+                "{}\n - {}",
+                settings.lang.bar.list,
+                settings.bar.join("\n - ")
+            ),
+        };
+        bot.send_message(dialogue.chat_id(), message).await?;
+        Self::start_commands(bot, dialogue, profiles).await
+    }
+
+    pub async fn bar_add(
+        bot: &AutoSend<Bot>,
+        dialogue: &LocalDialogue,
+        profiles: &ProfileStore,
+        user: ChatId,
+        names: &str,
+    ) -> ReturnTy {
+        let mut settings = profiles.get(user).await?;
+        let names = Self::split_names(names);
+        if names.is_empty() {
+            bot.send_message(dialogue.chat_id(), &settings.lang.bar.need_names)
+                .await?;
+            return Ok(());
+        }
+        for name in names.iter() {
+            if !settings
+                .bar
+                .iter()
+                .any(|have| have.eq_ignore_ascii_case(name))
+            {
+                settings.bar.push(name.clone());
+            }
+        }
+        bot.send_message(
+            dialogue.chat_id(),
+            format!("{} {}", settings.lang.bar.added, names.join(", ")),
+        )
+        .await?;
+        profiles.save(user, &settings).await
+    }
+
+    async fn bar_remove(
+        bot: &AutoSend<Bot>,
+        dialogue: &LocalDialogue,
+        profiles: &ProfileStore,
+        user: ChatId,
+        names: &str,
+    ) -> ReturnTy {
+        let mut settings = profiles.get(user).await?;
+        let names = Self::split_names(names);
+        if names.is_empty() {
+            bot.send_message(dialogue.chat_id(), &settings.lang.bar.need_names)
+                .await?;
+            return Ok(());
+        }
+        settings
+            .bar
+            .retain(|have| !names.iter().any(|name| have.eq_ignore_ascii_case(name)));
+        bot.send_message(
+            dialogue.chat_id(),
+            format!("{} {}", settings.lang.bar.removed, names.join(", ")),
+        )
+        .await?;
+        profiles.save(user, &settings).await
+    }
+
+    fn split_names(names: &str) -> Vec<String> {
+        names
+            .split(',')
+            .map(str::trim)
+            .filter(|name| !name.is_empty())
+            .map(str::to_string)
+            .collect()
+    }
+
+    async fn suggestion_bugs(
+        bot: &AutoSend<Bot>,
+        dialogue: &LocalDialogue,
+        profiles: &ProfileStore,
+    ) -> ReturnTy {
+        let settings = profiles.get(dialogue.chat_id()).await?;
         bot.send_message(dialogue.chat_id(), &settings.lang.todo.suggestion)
             .await?;
-        dialogue.update(State::Suggestion(settings)).await?;
+        dialogue.update(State::Suggestion).await?;
         Ok(())
     }
 
-    async fn random(bot: &AutoSend<Bot>, dialogue: &LocalDialogue) -> ReturnTy {
-        let mut drinks = Self::till_get(dialogue).await?;
+    async fn random(
+        bot: &AutoSend<Bot>,
+        dialogue: &LocalDialogue,
+        service: &DrinksService,
+        profiles: &ProfileStore,
+        history: &HistoryStore,
+        user: ChatId,
+    ) -> ReturnTy {
+        let UserSettings { lang, units, .. } = profiles.get(dialogue.chat_id()).await?;
+        let mut drinks = Self::till_get(lang, units, service).await?;
         let random_num = random_num_in_range(0, drinks.len());
-        MessageHandler::send_vec_with_photo(&vec![drinks.remove(random_num)], bot, dialogue)
-            .await?;
+        MessageHandler::send_vec_with_photo(
+            &[drinks.remove(random_num)],
+            bot,
+            dialogue,
+            profiles,
+            history,
+            user,
+        )
+        .await?;
         Ok(())
     }
-    async fn till_get(dialogue: &LocalDialogue) -> Result<Vec<LangDrink>, ErrorHandler> {
-        let UserSettings { lang, .. } = CommandsHandler::get_settings(dialogue).await?;
-        loop {
-          if let Some(drinks ) = DrinksService::search_by_first_letter(&random_english_character()?, lang.clone())
+    /// A sparse source has no drinks for many letters, so after a few of them
+    /// any drink of the source is taken.
+    pub async fn till_get(
+        lang: Lang,
+        units: Units,
+        service: &DrinksService,
+    ) -> Result<Vec<LangDrink>, ErrorHandler> {
+        for _ in 0..LETTER_ATTEMPTS {
+            if let Some(drinks) = service
+                .search_by_first_letter(&random_english_character()?, lang.clone(), units)
                 .await?
             {
-               return Ok(drinks)
-
+                return Ok(drinks);
             };
         }
-    }
-
-    pub async fn get_settings(dialogue: &LocalDialogue) -> Result<UserSettings, ErrorHandler> {
-        let state = dialogue.get().await?.expect("Untraceable code.");
-        match state.get_settings() {
-            Some(settings) => Ok(settings),
-            None => Ok(UserSettings::default()),
+        match service.random_drink(lang, units).await? {
+            Some(drink) => Ok(vec![drink]),
+            None => Err(ErrorHandler {
+                msg: "The source has no drinks.".to_string(),
+                ty: ErrorType::Service,
+            }),
         }
     }
 }
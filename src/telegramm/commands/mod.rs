@@ -1,2 +1,9 @@
+pub mod admin;
 pub mod command;
+pub mod daily;
+pub mod favorites;
 pub mod func;
+pub mod recommend;
+pub mod scale;
+pub mod shopping;
+pub mod vote;
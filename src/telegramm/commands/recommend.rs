@@ -0,0 +1,69 @@
+use teloxide::prelude::{AutoSend, Requester};
+use teloxide::types::ChatId;
+use teloxide::Bot;
+
+use crate::cocktails_api::services::coctail_service::DrinksService;
+use crate::error::error_handler::ErrorHandler;
+use crate::storage::history::{Event, HistoryStore};
+use crate::storage::profiles::ProfileStore;
+use crate::telegramm::commands::func::CommandsHandler;
+use crate::telegramm::messages::message_handler::MessageHandler;
+use crate::telegramm::settings::settings::UserSettings;
+use crate::telegramm::{LocalDialogue, ReturnTy};
+
+const RECOMMENDATIONS: usize = 10;
+/// Only the latest events shape the taste.
+const TASTE_HISTORY: usize = 50;
+
+pub struct RecommendHandler;
+
+impl RecommendHandler {
+    pub async fn recommend(
+        bot: &AutoSend<Bot>,
+        dialogue: &LocalDialogue,
+        service: &DrinksService,
+        profiles: &ProfileStore,
+        history: &HistoryStore,
+        user: ChatId,
+    ) -> ReturnTy {
+        let UserSettings { lang, units, .. } = profiles.get(dialogue.chat_id()).await?;
+        let events = history.events(user).await?;
+        let weights = events
+            .iter()
+            .take(TASTE_HISTORY)
+            .map(|(id, event)| (id.clone(), event.weight()))
+            .collect::<Vec<(String, i64)>>();
+        let taste = service.taste(&weights).await?;
+        if taste.is_empty() {
+            bot.send_message(dialogue.chat_id(), &lang.recommend.empty)
+                .await?;
+            return CommandsHandler::start_commands(bot, dialogue, profiles).await;
+        }
+        let seen = events
+            .into_iter()
+            .map(|(id, _)| id)
+            .collect::<Vec<String>>();
+        let result = service
+            .recommend(&taste, &seen, RECOMMENDATIONS, lang.clone(), units)
+            .await?;
+        if result.is_empty() {
+            bot.send_message(dialogue.chat_id(), &lang.recommend.nothing)
+                .await?;
+            CommandsHandler::start_commands(bot, dialogue, profiles).await
+        } else {
+            MessageHandler::send_vec_with_photo(&result, bot, dialogue, profiles, history, user)
+                .await
+        }
+    }
+
+    /// Returns the notice for the user.
+    pub async fn not_for_me(
+        profiles: &ProfileStore,
+        history: &HistoryStore,
+        user: ChatId,
+        id: &str,
+    ) -> Result<String, ErrorHandler> {
+        history.record(user, id, Event::Disliked).await?;
+        Ok(profiles.get(user).await?.lang.recommend.noted.clone())
+    }
+}
@@ -0,0 +1,139 @@
+use std::sync::Arc;
+
+use teloxide::payloads::SendMessageSetters;
+use teloxide::prelude::{AutoSend, Message, Requester};
+use teloxide::types::InlineKeyboardMarkup;
+use teloxide::Bot;
+
+use crate::cocktails_api::schemas::scaling::{LangBatch, LangServings};
+use crate::cocktails_api::services::coctail_service::DrinksService;
+use crate::error::error_handler::ErrorHandler;
+use crate::storage::profiles::ProfileStore;
+use crate::telegramm::buttons::inline::CallbackData;
+use crate::telegramm::commands::func::CommandsHandler;
+use crate::telegramm::settings::settings::UserSettings;
+use crate::telegramm::state::State;
+use crate::telegramm::{LocalDialogue, ReturnTy};
+
+const MAX_SERVINGS: u32 = 100;
+const MAX_GUESTS: u32 = 500;
+
+pub struct ScaleHandler;
+
+impl ScaleHandler {
+    pub async fn menu(
+        bot: &AutoSend<Bot>,
+        dialogue: &LocalDialogue,
+        profiles: &ProfileStore,
+        id: &str,
+    ) -> ReturnTy {
+        let UserSettings { lang, .. } = profiles.get(dialogue.chat_id()).await?;
+        let id = id.to_string();
+        let keyboard = InlineKeyboardMarkup::new(vec![
+            vec![
+                CallbackData::Servings(id.clone(), 2).button(&lang),
+                CallbackData::Servings(id.clone(), 4).button(&lang),
+                CallbackData::ScaleCustom(id.clone()).button(&lang),
+            ],
+            vec![CallbackData::Batch(id).button(&lang)],
+        ]);
+        bot.send_message(dialogue.chat_id(), &lang.scale.title)
+            .reply_markup(keyboard)
+            .await?;
+        Ok(())
+    }
+
+    /// The number comes with the next message.
+    pub async fn ask(
+        bot: &AutoSend<Bot>,
+        dialogue: &LocalDialogue,
+        profiles: &ProfileStore,
+        state: State,
+    ) -> ReturnTy {
+        let UserSettings { lang, .. } = profiles.get(dialogue.chat_id()).await?;
+        let question = match state {
+            State::Batch(_) => &lang.scale.ask_guests,
+            _ => &lang.scale.ask_servings,
+        };
+        bot.send_message(dialogue.chat_id(), question).await?;
+        dialogue.update(state).await?;
+        Ok(())
+    }
+
+    pub async fn send_servings(
+        bot: &AutoSend<Bot>,
+        dialogue: &LocalDialogue,
+        service: &DrinksService,
+        profiles: &ProfileStore,
+        id: &str,
+        servings: u32,
+    ) -> ReturnTy {
+        let UserSettings { lang, units, .. } = profiles.get(dialogue.chat_id()).await?;
+        let text = match service.lookup_by_id(id, lang.clone(), units).await? {
+            Some(drink) => LangServings { drink, servings }.to_string(),
+            None => lang.fail_messages.non_results.clone(),
+        };
+        bot.send_message(dialogue.chat_id(), text).await?;
+        Ok(())
+    }
+
+    pub async fn servings(
+        message: Message,
+        bot: AutoSend<Bot>,
+        dialogue: LocalDialogue,
+        service: Arc<DrinksService>,
+        profiles: Arc<ProfileStore>,
+        id: String,
+    ) -> ReturnTy {
+        let servings =
+            match Self::number(&bot, &dialogue, &profiles, &message, MAX_SERVINGS).await? {
+                Some(servings) => servings,
+                None => return Ok(()),
+            };
+        Self::send_servings(&bot, &dialogue, &service, &profiles, &id, servings).await?;
+        CommandsHandler::start_commands(&bot, &dialogue, &profiles).await
+    }
+
+    pub async fn batch(
+        message: Message,
+        bot: AutoSend<Bot>,
+        dialogue: LocalDialogue,
+        service: Arc<DrinksService>,
+        profiles: Arc<ProfileStore>,
+        id: String,
+    ) -> ReturnTy {
+        let guests = match Self::number(&bot, &dialogue, &profiles, &message, MAX_GUESTS).await? {
+            Some(guests) => guests,
+            None => return Ok(()),
+        };
+        let UserSettings { lang, units, .. } = profiles.get(dialogue.chat_id()).await?;
+        let text = match service.lookup_by_id(&id, lang.clone(), units).await? {
+            Some(drink) => LangBatch { drink, guests }.to_string(),
+            None => lang.fail_messages.non_results.clone(),
+        };
+        bot.send_message(dialogue.chat_id(), text).await?;
+        CommandsHandler::start_commands(&bot, &dialogue, &profiles).await
+    }
+
+    /// Asks again until the number is in `1..=max`.
+    async fn number(
+        bot: &AutoSend<Bot>,
+        dialogue: &LocalDialogue,
+        profiles: &ProfileStore,
+        message: &Message,
+        max: u32,
+    ) -> Result<Option<u32>, ErrorHandler> {
+        match message.text().map(|text| text.trim().parse::<u32>()) {
+            Some(Ok(number)) if (1..=max).contains(&number) => Ok(Some(number)),
+            _ => {
+                let UserSettings { lang, .. } = profiles.get(dialogue.chat_id()).await?;
+                bot.send_message(
+                    dialogue.chat_id(),
+                    format!("1-{}, {}", max, lang.fail_messages.need_number),
+                )
+                .await?;
+                Ok(None)
+            }
+        }
+    }
+}
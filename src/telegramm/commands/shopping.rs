@@ -0,0 +1,141 @@
+use teloxide::payloads::SendMessageSetters;
+use teloxide::prelude::{AutoSend, Requester};
+use teloxide::types::{ChatId, InlineKeyboardMarkup, InputFile};
+use teloxide::Bot;
+
+use crate::cocktails_api::services::coctail_service::DrinksService;
+use crate::error::error_handler::ErrorHandler;
+use crate::storage::profiles::ProfileStore;
+use crate::telegramm::buttons::inline::CallbackData;
+use crate::telegramm::messages::message_handler::TELEGRAMM_CHAR_LIMIT;
+use crate::telegramm::settings::settings::Favorite;
+use crate::telegramm::{LocalDialogue, ReturnTy};
+
+const FILE_NAME: &str = "shopping_list.csv";
+
+/// The shopping list belongs to the user, like the favourites and the bar.
+pub struct ShoppingHandler;
+
+impl ShoppingHandler {
+    /// Picked drinks with the buttons to send the list.
+    pub async fn shopping(
+        bot: &AutoSend<Bot>,
+        dialogue: &LocalDialogue,
+        profiles: &ProfileStore,
+        user: ChatId,
+    ) -> ReturnTy {
+        let settings = profiles.get(user).await?;
+        let lang = &settings.lang;
+        if settings.shopping.is_empty() {
+            bot.send_message(dialogue.chat_id(), &lang.shopping.empty)
+                .await?;
+            return Ok(());
+        }
+        let keyboard = InlineKeyboardMarkup::new(vec![
+            vec![
+                CallbackData::ShopSend.button(lang),
+                CallbackData::ShopFile.button(lang),
+            ],
+            vec![CallbackData::ShopClear.button(lang)],
+        ]);
+        let names = settings
+            .shopping
+            .iter()
+            .map(|drink| drink.name.as_str())
+            .collect::<Vec<&str>>();
+        bot.send_message(
+            dialogue.chat_id(),
+            format!("{}\n - {}", lang.shopping.selected, names.join("\n - ")),
+        )
+        .reply_markup(keyboard)
+        .await?;
+        Ok(())
+    }
+
+    /// Returns the notice for the user.
+    pub async fn add(
+        service: &DrinksService,
+        profiles: &ProfileStore,
+        user: ChatId,
+        id: &str,
+    ) -> Result<String, ErrorHandler> {
+        let mut settings = profiles.get(user).await?;
+        if settings.shopping.iter().any(|drink| drink.id == id) {
+            return Ok(settings.lang.shopping.already.clone());
+        }
+        let drink = service
+            .lookup_by_id(id, settings.lang.clone(), settings.units)
+            .await?;
+        let notice = match drink {
+            Some(drink) => {
+                settings.shopping.push(Favorite {
+                    id: id.to_string(),
+                    name: drink.drink.name,
+                });
+                settings.lang.shopping.added.clone()
+            }
+            None => return Ok(settings.lang.fail_messages.non_results.clone()),
+        };
+        profiles.save(user, &settings).await?;
+        Ok(notice)
+    }
+
+    pub async fn add_favorites(
+        bot: &AutoSend<Bot>,
+        dialogue: &LocalDialogue,
+        profiles: &ProfileStore,
+        user: ChatId,
+    ) -> ReturnTy {
+        let mut settings = profiles.get(user).await?;
+        for favorite in settings.favorites.clone() {
+            if !settings.shopping.contains(&favorite) {
+                settings.shopping.push(favorite);
+            }
+        }
+        profiles.save(user, &settings).await?;
+        Self::shopping(bot, dialogue, profiles, user).await
+    }
+
+    pub async fn clear(profiles: &ProfileStore, user: ChatId) -> Result<String, ErrorHandler> {
+        let mut settings = profiles.get(user).await?;
+        settings.shopping.clear();
+        profiles.save(user, &settings).await?;
+        Ok(settings.lang.shopping.cleared.clone())
+    }
+
+    /// A list which does not fit into a message goes as a document anyway.
+    pub async fn send(
+        bot: &AutoSend<Bot>,
+        dialogue: &LocalDialogue,
+        service: &DrinksService,
+        profiles: &ProfileStore,
+        user: ChatId,
+        as_file: bool,
+    ) -> ReturnTy {
+        let settings = profiles.get(user).await?;
+        if settings.shopping.is_empty() {
+            bot.send_message(dialogue.chat_id(), &settings.lang.shopping.empty)
+                .await?;
+            return Ok(());
+        }
+        let ids = settings
+            .shopping
+            .iter()
+            .map(|drink| drink.id.clone())
+            .collect::<Vec<String>>();
+        let shopping = service
+            .shopping_list(&ids, &settings.bar, settings.lang.clone(), settings.units)
+            .await?;
+        let text = shopping.to_string();
+        match as_file || text.chars().count() >= TELEGRAMM_CHAR_LIMIT {
+            true => {
+                let file = InputFile::memory(shopping.csv().into_bytes()).file_name(FILE_NAME);
+                bot.send_document(dialogue.chat_id(), file).await?;
+            }
+            false => {
+                bot.send_message(dialogue.chat_id(), text).await?;
+            }
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,200 @@
+use std::sync::Arc;
+use std::time::Duration;
+
+use itertools::Itertools;
+use teloxide::payloads::{EditMessageReplyMarkupSetters, SendMessageSetters};
+use teloxide::prelude::{AutoSend, Requester};
+use teloxide::types::{ChatId, InlineKeyboardButton, InlineKeyboardMarkup, InputFile, User};
+use teloxide::Bot;
+use url::Url;
+
+use crate::cocktails_api::services::coctail_service::DrinksService;
+use crate::error::error_handler::ErrorHandler;
+use crate::storage;
+use crate::storage::profiles::ProfileStore;
+use crate::storage::votes::{Vote, VoteOption, VoteStore};
+use crate::telegramm::buttons::inline::CallbackData;
+use crate::telegramm::commands::func::CommandsHandler;
+use crate::telegramm::messages::group::GroupHandler;
+use crate::telegramm::settings::settings::UserSettings;
+use crate::telegramm::{LocalDialogue, ReturnTy};
+use crate::utils::helpers::random_num_in_range;
+
+const VOTE_OPTIONS: usize = 4;
+/// Seconds a vote stays open.
+const VOTE_TIMEOUT: i64 = 600;
+const WATCH_INTERVAL: u64 = 15;
+
+pub struct VoteHandler;
+
+impl VoteHandler {
+    pub async fn start(
+        bot: &AutoSend<Bot>,
+        dialogue: &LocalDialogue,
+        service: &DrinksService,
+        profiles: &ProfileStore,
+        votes: &VoteStore,
+    ) -> ReturnTy {
+        let UserSettings { lang, units, .. } = profiles.get(dialogue.chat_id()).await?;
+        let mut drinks = CommandsHandler::till_get(lang.clone(), units, service).await?;
+        let mut options = vec![];
+        while options.len() < VOTE_OPTIONS && !drinks.is_empty() {
+            let drink = drinks.remove(random_num_in_range(0, drinks.len()));
+            if let Some(id) = drink.drink.id {
+                options.push(VoteOption {
+                    id,
+                    name: drink.drink.name,
+                });
+            }
+        }
+        if options.len() < 2 {
+            bot.send_message(dialogue.chat_id(), &lang.fail_messages.non_results)
+                .await?;
+            return Ok(());
+        }
+        // The bot itself is a member of the chat too.
+        let voters = (bot.get_chat_member_count(dialogue.chat_id()).await? as i64 - 1).max(1);
+        let id = votes
+            .create(
+                dialogue.chat_id().0,
+                &options,
+                voters,
+                storage::now() + VOTE_TIMEOUT,
+            )
+            .await?;
+        let message = bot
+            .send_message(dialogue.chat_id(), &lang.vote.title)
+            .reply_markup(Self::keyboard(id, &options, &vec![0; options.len()]))
+            .await?;
+        votes.set_message(id, message.id).await?;
+        Ok(())
+    }
+
+    /// Returns the notice for the voter.
+    pub async fn cast(
+        bot: &AutoSend<Bot>,
+        service: &DrinksService,
+        profiles: &ProfileStore,
+        votes: &VoteStore,
+        user: &User,
+        id: i64,
+        option: usize,
+    ) -> Result<String, ErrorHandler> {
+        let vote = votes.get(id).await?;
+        let chat_id = vote
+            .as_ref()
+            .map(|vote| ChatId(vote.chat_id))
+            .unwrap_or_else(|| GroupHandler::user_chat(user));
+        let UserSettings { lang, .. } = profiles.get(chat_id).await?;
+        let vote = match vote {
+            Some(vote) if option < vote.options.len() && vote.closes_at > storage::now() => vote,
+            _ => return Ok(lang.vote.over.clone()),
+        };
+        if !votes.cast(id, user.id.0 as i64, option).await? {
+            return Ok(lang.vote.counted.clone());
+        }
+        let tallies = votes.tallies(&vote).await?;
+        if tallies.iter().sum::<usize>() as i64 >= vote.voters {
+            Self::close(bot, service, profiles, votes, vote).await?;
+        } else {
+            bot.edit_message_reply_markup(chat_id, vote.message_id)
+                .reply_markup(Self::keyboard(id, &vote.options, &tallies))
+                .await?;
+        }
+        Ok(lang.vote.counted.clone())
+    }
+
+    /// Posts the results and the recipe of the winner, a closed vote is skipped.
+    pub async fn close(
+        bot: &AutoSend<Bot>,
+        service: &DrinksService,
+        profiles: &ProfileStore,
+        votes: &VoteStore,
+        vote: Vote,
+    ) -> ReturnTy {
+        if !votes.close(vote.id).await? {
+            return Ok(());
+        }
+        let chat_id = ChatId(vote.chat_id);
+        let UserSettings { lang, units, .. } = profiles.get(chat_id).await?;
+        let tallies = votes.tallies(&vote).await?;
+        if vote.message_id != 0 {
+            let results = vote
+                .options
+                .iter()
+                .zip(tallies.iter())
+                .map(|(option, count)| format!("{} - {}", option.name, count))
+                .join("\n");
+            bot.edit_message_text(
+                chat_id,
+                vote.message_id,
+                format!("{}\n{}", lang.vote.closed, results),
+            )
+            .await?;
+        }
+        let winner = match Vote::winner(&tallies) {
+            Some(winner) => &vote.options[winner],
+            None => {
+                bot.send_message(chat_id, &lang.vote.nobody).await?;
+                return Ok(());
+            }
+        };
+        match service
+            .lookup_by_id(&winner.id, lang.clone(), units)
+            .await?
+        {
+            Some(drink) => {
+                bot.send_message(
+                    chat_id,
+                    format!("{} {}\n\n{}", lang.vote.winner, winner.name, drink),
+                )
+                .await?;
+                if let Some(image) = &drink.drink.image {
+                    bot.send_photo(chat_id, InputFile::url(Url::parse(image)?))
+                        .await?;
+                }
+            }
+            None => {
+                bot.send_message(chat_id, format!("{} {}", lang.vote.winner, winner.name))
+                    .await?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Closes the votes which ran out of time, including the ones started before a restart.
+    pub async fn watch(
+        bot: AutoSend<Bot>,
+        service: Arc<DrinksService>,
+        profiles: Arc<ProfileStore>,
+        votes: Arc<VoteStore>,
+    ) {
+        let mut interval = tokio::time::interval(Duration::from_secs(WATCH_INTERVAL));
+        loop {
+            interval.tick().await;
+            let expired = match votes.expired(storage::now()).await {
+                Ok(expired) => expired,
+                Err(error) => {
+                    log::error!("Fail to load expired votes: {:?}", error);
+                    continue;
+                }
+            };
+            for vote in expired {
+                if let Err(error) = Self::close(&bot, &service, &profiles, &votes, vote).await {
+                    log::error!("Fail to close the vote: {:?}", error);
+                }
+            }
+        }
+    }
+
+    fn keyboard(id: i64, options: &[VoteOption], tallies: &[usize]) -> InlineKeyboardMarkup {
+        InlineKeyboardMarkup::new(options.iter().zip(tallies.iter()).enumerate().map(
+            |(num, (option, count))| {
+                vec![InlineKeyboardButton::callback(
+                    format!("{} ({})", option.name, count),
+                    CallbackData::Vote(id, num).to_string(),
+                )]
+            },
+        ))
+    }
+}
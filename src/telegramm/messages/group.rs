@@ -0,0 +1,166 @@
+use std::sync::Arc;
+
+use teloxide::prelude::{AutoSend, Message, Requester, Update};
+use teloxide::types::{ChatId, Me, MediaKind, MessageKind, User};
+use teloxide::Bot;
+
+use crate::error::error_handler::ErrorHandler;
+use crate::localization::lang::Lang;
+use crate::storage::profiles::ProfileStore;
+use crate::telegramm::settings::settings::UserSettings;
+use crate::telegramm::state::State;
+
+/// In groups the bot reacts only to commands, mentions and replies to its messages.
+/// Chat settings are kept under the chat id, personal data under the user id.
+pub struct GroupHandler;
+
+impl GroupHandler {
+    /// The message with the mention cut out, `None` when the message is not for the bot.
+    /// Media without a caption goes through when the dialogue waits for it.
+    pub fn addressed(mut message: Message, me: Me, state: State) -> Option<Message> {
+        if message.chat.is_private() {
+            return Some(message);
+        }
+        let username = me.username();
+        let text = match message.text().or_else(|| message.caption()) {
+            Some(text) => text.to_string(),
+            None if Self::expects_media(&state) => return Some(message),
+            None => return None,
+        };
+        if text.starts_with('/') {
+            return match Self::foreign_command(&text, username) {
+                true => None,
+                false => Some(message),
+            };
+        }
+        if let Some(text) = Self::strip_mention(&text, username) {
+            if let MessageKind::Common(common) = &mut message.kind {
+                match &mut common.media_kind {
+                    MediaKind::Text(media) => {
+                        media.text = text;
+                        media.entities.clear();
+                    }
+                    MediaKind::Photo(media) => {
+                        media.caption = Some(text);
+                        media.caption_entities.clear();
+                    }
+                    _ => {}
+                }
+            }
+            return Some(message);
+        }
+        let reply_to_bot = message
+            .reply_to_message()
+            .and_then(|reply| reply.from())
+            .map(|user| user.id == me.user.id)
+            .unwrap_or(false);
+        match reply_to_bot {
+            true => Some(message),
+            false => None,
+        }
+    }
+
+    /// Chat of the user, personal data lives there even when the bot is used in a group.
+    pub fn user_chat(user: &User) -> ChatId {
+        ChatId(user.id.0 as i64)
+    }
+
+    pub fn message_user_chat(message: &Message) -> ChatId {
+        message
+            .from()
+            .map(Self::user_chat)
+            .unwrap_or(message.chat.id)
+    }
+
+    /// A new chat speaks the language Telegram reports for the first user who writes.
+    /// The store remembers the introduced chats, so it is written once per chat.
+    pub async fn introduce(update: Update, profiles: Arc<ProfileStore>) -> bool {
+        if let (Some(chat), Some(user)) = (update.chat(), update.user()) {
+            let settings = || UserSettings {
+                lang: Lang::from_code(user.language_code.as_deref()),
+                ..UserSettings::default()
+            };
+            if let Err(error) = profiles.create_once(chat.id, settings).await {
+                log::error!("Fail to create the profile: {:?}", error);
+            }
+        }
+        true
+    }
+
+    /// Everybody configures a private chat, only admins configure a group.
+    pub async fn can_configure(
+        bot: &AutoSend<Bot>,
+        chat_id: ChatId,
+        user: &User,
+    ) -> Result<bool, ErrorHandler> {
+        if chat_id == Self::user_chat(user) {
+            return Ok(true);
+        }
+        Ok(bot.get_chat_member(chat_id, user.id).await?.is_privileged())
+    }
+
+    fn expects_media(state: &State) -> bool {
+        matches!(state, State::Suggestion)
+    }
+
+    fn foreign_command(text: &str, username: &str) -> bool {
+        let command = text.split_whitespace().next().unwrap_or_default();
+        match command.split_once('@') {
+            Some((_, bot)) => !bot.eq_ignore_ascii_case(username),
+            None => false,
+        }
+    }
+
+    fn strip_mention(text: &str, username: &str) -> Option<String> {
+        // Usernames are ASCII, so the byte positions are the same in any case.
+        let mention = format!("@{}", username);
+        let position = (0..=text.len().saturating_sub(mention.len())).find(|position| {
+            text.is_char_boundary(*position)
+                && text.as_bytes()[*position..]
+                    .get(..mention.len())
+                    .map(|bytes| bytes.eq_ignore_ascii_case(mention.as_bytes()))
+                    .unwrap_or(false)
+        })?;
+        Some(
+            format!(
+                "{} {}",
+                text[..position].trim(),
+                text[position + mention.len()..].trim()
+            )
+            .trim()
+            .to_string(),
+        )
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::telegramm::messages::group::GroupHandler;
+    use crate::telegramm::state::State;
+
+    #[test]
+    fn test_strip_mention() {
+        assert_eq!(
+            GroupHandler::strip_mention("@Bar_Bot Negroni", "bar_bot"),
+            Some("Negroni".to_string())
+        );
+        assert_eq!(
+            GroupHandler::strip_mention("Vodka, @bar_bot", "bar_bot"),
+            Some("Vodka,".to_string())
+        );
+        assert_eq!(GroupHandler::strip_mention("Negroni", "bar_bot"), None);
+    }
+
+    #[test]
+    fn test_expects_media() {
+        assert!(GroupHandler::expects_media(&State::Suggestion));
+        assert!(!GroupHandler::expects_media(&State::Start));
+    }
+
+    #[test]
+    fn test_foreign_command() {
+        assert!(!GroupHandler::foreign_command("/random", "bar_bot"));
+        assert!(!GroupHandler::foreign_command("/random@bar_bot", "bar_bot"));
+        assert!(GroupHandler::foreign_command("/start@other_bot", "bar_bot"));
+    }
+}
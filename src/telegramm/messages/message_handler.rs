@@ -1,22 +1,29 @@
 use std::fmt::Display;
+use std::sync::Arc;
 
-use teloxide::payloads::SendMessageSetters;
+use teloxide::payloads::{EditMessageTextSetters, SendMessageSetters};
 use teloxide::prelude::{AutoSend, Message, Requester};
-use teloxide::types::InputFile;
+use teloxide::types::{ChatId, InputFile, InputMedia, InputMediaPhoto};
 use teloxide::Bot;
 use url::Url;
 
 use crate::cocktails_api::schemas::drink::WithPhoto;
 use crate::cocktails_api::services::coctail_service::DrinksService;
-use crate::{ErrorHandler, ErrorType};
+use crate::localization::lang::Lang;
+use crate::storage::feedback::{Feedback, FeedbackStatus, FeedbackStore};
+use crate::storage::history::{Event, HistoryStore};
+use crate::storage::profiles::ProfileStore;
 use crate::telegramm::buttons::callback_handler::CallBackHandler;
+use crate::telegramm::buttons::inline::{actions_keyboard, WithActions};
 use crate::telegramm::buttons::keyboard::{make_keyboard, standard_keyboard_as_str_vec};
 use crate::telegramm::commands::func::CommandsHandler;
+use crate::telegramm::messages::group::GroupHandler;
+use crate::telegramm::messages::pages::{PageItem, Pages};
 use crate::telegramm::settings::settings::{SettingsKeyboard, UserSettings};
 use crate::telegramm::state::State;
 use crate::telegramm::{LocalDialogue, ReturnTy};
-use crate::utils::helpers::{random_num_in_range, vec_to_string, write_to_file};
 use crate::utils::unicod::Emojis;
+use crate::{ErrorHandler, ErrorType};
 
 pub const TELEGRAMM_CHAR_LIMIT: usize = 4096;
 
@@ -27,8 +34,9 @@ impl MessageHandler {
         message: Message,
         bot: AutoSend<Bot>,
         dialogue: LocalDialogue,
+        profiles: Arc<ProfileStore>,
     ) -> ReturnTy {
-        let settings = CommandsHandler::get_settings(&dialogue).await?;
+        let settings = profiles.get(dialogue.chat_id()).await?;
         bot.send_message(
             dialogue.chat_id(),
             format!(
@@ -40,37 +48,45 @@ impl MessageHandler {
             ),
         )
         .await?;
-        CommandsHandler::start_commands(&bot, &dialogue).await?;
+        CommandsHandler::start_commands(&bot, &dialogue, &profiles).await?;
         Ok(())
     }
     pub async fn settings(
         message: Message,
         bot: AutoSend<Bot>,
         dialogue: LocalDialogue,
+        profiles: Arc<ProfileStore>,
+        params: SettingsKeyboard,
     ) -> ReturnTy {
-        if let Some(message) = message.text() {
-            let settings = if let State::SettingsUpdate(mut settings, SettingsKeyboard::Name) =
-                dialogue.get().await?.expect("Untraceable code.")
-            {
-                if message.len() > 3 && message.len() < 15 {
-                    settings.name = Some(message.to_owned());
-                    settings
-                } else {
-                    bot.send_message(
-                        dialogue.chat_id(),
-                        format!(
-                            "{}, {}",
-                            &settings.name.unwrap_or_else(|| "".to_string()),
-                            &settings.lang.settings_descriptions.limit_name
-                        ),
-                    )
+        if let Some(text) = message.text() {
+            let mut settings = profiles.get(dialogue.chat_id()).await?;
+            let allowed = match message.from() {
+                Some(user) => GroupHandler::can_configure(&bot, dialogue.chat_id(), user).await?,
+                None => false,
+            };
+            if !allowed {
+                bot.send_message(dialogue.chat_id(), &settings.lang.fail_messages.admins_only)
                     .await?;
-                    return Ok(());
+                return Ok(());
+            }
+            match params {
+                SettingsKeyboard::Name => {
+                    if text.len() > 3 && text.len() < 15 {
+                        settings.name = Some(text.to_owned());
+                    } else {
+                        bot.send_message(
+                            dialogue.chat_id(),
+                            format!(
+                                "{}, {}",
+                                &settings.name.unwrap_or_else(|| "".to_string()),
+                                &settings.lang.settings_descriptions.limit_name
+                            ),
+                        )
+                        .await?;
+                        return Ok(());
+                    }
                 }
-            } else if let State::SettingsUpdate(mut settings, SettingsKeyboard::MessageLimit) =
-                dialogue.get().await?.expect("Untraceable code.")
-            {
-                match message.parse::<u32>() {
+                SettingsKeyboard::MessageLimit => match text.parse::<u32>() {
                     Ok(limit) => {
                         if limit > 2 && limit < 81 {
                             settings.limit_of_messages = limit;
@@ -86,7 +102,6 @@ impl MessageHandler {
                             .await?;
                             return Ok(());
                         }
-                        settings
                     }
                     Err(_) => {
                         bot.send_message(
@@ -101,31 +116,74 @@ impl MessageHandler {
                         .await?;
                         return Ok(());
                     }
+                },
+                _ => {
+                    return Err(ErrorHandler {
+                        msg: "Wrong command".to_string(),
+                        ty: ErrorType::User,
+                    })
                 }
-            } else {
-                return Err(ErrorHandler { msg: "Wrong command".to_string(), ty: ErrorType::User })
             };
+            profiles.save(dialogue.chat_id(), &settings).await?;
             let keyboard = make_keyboard(&standard_keyboard_as_str_vec(&settings.lang));
             bot.send_message(dialogue.chat_id(), &settings.lang.send_commands)
                 .reply_markup(keyboard)
                 .await?;
-            dialogue.update(State::CallBack(settings)).await?;
+            dialogue.update(State::CallBack).await?;
         }
 
         Ok(())
     }
+    /// Text or a screenshot with a caption, the author is kept to reply later.
+    /// A screenshot sent as a file comes as an image document.
     pub async fn suggestion(
         message: Message,
         bot: AutoSend<Bot>,
         dialogue: LocalDialogue,
+        profiles: Arc<ProfileStore>,
+        feedback: Arc<FeedbackStore>,
     ) -> ReturnTy {
-        if let Some(message) = message.text() {
-            write_to_file(message)?;
+        let settings = profiles.get(dialogue.chat_id()).await?;
+        let photo = message
+            .photo()
+            .and_then(|sizes| sizes.last())
+            .map(|size| size.file_id.clone())
+            .or_else(|| {
+                message
+                    .document()
+                    .filter(|document| {
+                        document
+                            .mime_type
+                            .as_ref()
+                            .is_some_and(|mime| mime.type_() == "image")
+                    })
+                    .map(|document| document.file_id.clone())
+            });
+        let text = message.text().or_else(|| message.caption());
+        if text.is_none() && photo.is_none() {
+            bot.send_message(dialogue.chat_id(), &settings.lang.todo.suggestion)
+                .await?;
+            return Ok(());
         }
-        let settings = CommandsHandler::get_settings(&dialogue).await?;
+        let user = message.from();
+        feedback
+            .add(&Feedback {
+                id: 0,
+                user_id: user
+                    .map(|user| user.id.0 as i64)
+                    .unwrap_or(message.chat.id.0),
+                username: user.and_then(|user| user.username.clone()),
+                chat_id: message.chat.id.0,
+                lang: settings.lang.code().to_string(),
+                text: text.unwrap_or_default().to_string(),
+                photo,
+                status: FeedbackStatus::New,
+                created_at: 0,
+            })
+            .await?;
         bot.send_message(dialogue.chat_id(), &settings.lang.fail_messages.suggestion)
             .await?;
-        CommandsHandler::start_commands(&bot, &dialogue).await?;
+        CommandsHandler::start_commands(&bot, &dialogue, &profiles).await?;
         Ok(())
     }
 
@@ -133,14 +191,19 @@ impl MessageHandler {
         message: Message,
         bot: AutoSend<Bot>,
         dialogue: LocalDialogue,
+        service: Arc<DrinksService>,
+        profiles: Arc<ProfileStore>,
+        history: Arc<HistoryStore>,
     ) -> ReturnTy {
+        let user = GroupHandler::message_user_chat(&message);
         if let Some(message) = message.text() {
-            let UserSettings { lang, .. } = CommandsHandler::get_settings(&dialogue).await?;
+            let UserSettings { lang, units, .. } = profiles.get(dialogue.chat_id()).await?;
             let fail_message = lang.fail_messages.non_results.clone();
-            if let Some(result) = DrinksService::get_drink_by_name(message, lang).await? {
-                Self::send_vec_with_photo(&result, &bot, &dialogue).await?;
+            if let Some(result) = service.get_drink_by_name(message, lang, units).await? {
+                Self::send_vec_with_photo(&result, &bot, &dialogue, &profiles, &history, user)
+                    .await?;
             } else {
-                Self::send_wrong_message(&fail_message, &bot, &dialogue).await?;
+                Self::send_wrong_message(&fail_message, &bot, &dialogue, &profiles).await?;
             }
         }
         Ok(())
@@ -149,18 +212,25 @@ impl MessageHandler {
         message: Message,
         bot: AutoSend<Bot>,
         dialogue: LocalDialogue,
+        service: Arc<DrinksService>,
+        profiles: Arc<ProfileStore>,
+        history: Arc<HistoryStore>,
     ) -> ReturnTy {
+        let user = GroupHandler::message_user_chat(&message);
         if let Some(message) = message.text() {
-            let user_setting = CommandsHandler::get_settings(&dialogue).await?;
-            if let Some(result) =
-                DrinksService::find_by_category(message, user_setting.lang.clone()).await?
+            let user_setting = profiles.get(dialogue.chat_id()).await?;
+            if let Some(result) = service
+                .find_by_category(message, user_setting.lang.clone())
+                .await?
             {
-                Self::send_vec_with_photo(&result, &bot, &dialogue).await?;
+                Self::send_vec_with_photo(&result, &bot, &dialogue, &profiles, &history, user)
+                    .await?;
             } else {
                 Self::send_wrong_message(
                     &user_setting.lang.fail_messages.wrong_category,
                     &bot,
                     &dialogue,
+                    &profiles,
                 )
                 .await?;
             }
@@ -171,30 +241,91 @@ impl MessageHandler {
         message: Message,
         bot: AutoSend<Bot>,
         dialogue: LocalDialogue,
+        service: Arc<DrinksService>,
+        profiles: Arc<ProfileStore>,
+        history: Arc<HistoryStore>,
     ) -> ReturnTy {
+        let user = GroupHandler::message_user_chat(&message);
         if let Some(message) = message.text() {
-            let user_settings = CommandsHandler::get_settings(&dialogue).await?;
-            if let Some(result) =
-                DrinksService::find_by_ingredient(message, user_settings.lang.clone()).await?
-            {
-                Self::send_vec_with_photo(&result, &bot, &dialogue).await?;
+            let mut names = match dialogue.get().await? {
+                Some(State::WithIngredient(selected)) => selected,
+                _ => vec![],
+            };
+            for name in message.split(',').map(str::trim) {
+                if !name.is_empty() && !names.iter().any(|have| have.eq_ignore_ascii_case(name)) {
+                    names.push(name.to_string());
+                }
+            }
+            if names.len() > 1 {
+                return Self::send_matches(
+                    names, &bot, &dialogue, &service, &profiles, &history, user,
+                )
+                .await;
+            }
+            let user_settings = profiles.get(dialogue.chat_id()).await?;
+            let result = match names.first() {
+                Some(name) => {
+                    service
+                        .find_by_ingredient(name, user_settings.lang.clone())
+                        .await?
+                }
+                None => None,
+            };
+            if let Some(result) = result {
+                Self::send_vec_with_photo(&result, &bot, &dialogue, &profiles, &history, user)
+                    .await?;
             } else {
                 Self::send_wrong_message(
                     &user_settings.lang.fail_messages.wrong_ingredient,
                     &bot,
                     &dialogue,
+                    &profiles,
                 )
                 .await?;
             }
         }
         Ok(())
     }
+
+    /// Drinks ranked by how many of the given ingredients they use.
+    pub async fn send_matches(
+        names: Vec<String>,
+        bot: &AutoSend<Bot>,
+        dialogue: &LocalDialogue,
+        service: &DrinksService,
+        profiles: &ProfileStore,
+        history: &HistoryStore,
+        user: ChatId,
+    ) -> ReturnTy {
+        let settings = profiles.get(dialogue.chat_id()).await?;
+        let result = service
+            .find_by_ingredients(
+                &names,
+                settings.limit_of_messages as usize,
+                settings.lang.clone(),
+                settings.units,
+            )
+            .await?;
+        if result.is_empty() {
+            Self::send_wrong_message(
+                &settings.lang.fail_messages.wrong_ingredient,
+                bot,
+                dialogue,
+                profiles,
+            )
+            .await?;
+        } else {
+            Self::send_vec_with_photo(&result, bot, dialogue, profiles, history, user).await?;
+        }
+        Ok(())
+    }
     async fn send_wrong_message(
         message: &str,
         bot: &AutoSend<Bot>,
         dialogue: &LocalDialogue,
+        profiles: &ProfileStore,
     ) -> ReturnTy {
-        let settings = CommandsHandler::get_settings(dialogue).await?;
+        let settings = profiles.get(dialogue.chat_id()).await?;
         bot.send_message(
             dialogue.chat_id(),
             format!(
@@ -208,61 +339,169 @@ impl MessageHandler {
         Ok(())
     }
     pub async fn send_vec_with_photo<T>(
-        to_send: &Vec<T>,
+        to_send: &[T],
         bot: &AutoSend<Bot>,
         dialogue: &LocalDialogue,
+        profiles: &ProfileStore,
+        history: &HistoryStore,
+        user: ChatId,
     ) -> ReturnTy
     where
-        T: Display + WithPhoto,
+        T: Display + WithPhoto + WithActions,
     {
-        let settings = CommandsHandler::get_settings(dialogue).await?;
-        if settings.send_image {
-            let (start_el, final_el) =
-                Self::get_range(to_send.len(), settings.limit_of_messages as usize);
-            for result in to_send[start_el..final_el].iter() {
-                CallBackHandler::send_message(&result.to_string(), bot, dialogue).await?;
-                if let Some(image) = result.get_url() {
-                    bot.send_photo(dialogue.chat_id(), InputFile::url(Url::parse(&image)?))
-                        .await?;
-                }
+        let settings = profiles.get(dialogue.chat_id()).await?;
+        let mut pages = Pages::new(
+            to_send,
+            settings.limit_of_messages as usize,
+            settings.send_image,
+        );
+        let (text, keyboard) = pages.render(&settings.lang);
+        let mut request = bot.send_message(dialogue.chat_id(), text);
+        if let Some(keyboard) = keyboard {
+            request = request.reply_markup(keyboard);
+        }
+        let message = request.await?;
+        Self::show_photos(bot, dialogue, &mut pages).await?;
+        Self::record_viewed(history, user, &pages).await?;
+        CommandsHandler::start_commands(bot, dialogue, profiles).await?;
+        if pages.len() > 1 {
+            pages.message_id = message.id;
+            dialogue.update(State::Paging(pages)).await?;
+        }
+        Ok(())
+    }
+
+    /// Edits the paged results in place, the results are taken from the dialogue.
+    pub async fn turn_page(
+        bot: &AutoSend<Bot>,
+        dialogue: &LocalDialogue,
+        profiles: &ProfileStore,
+        history: &HistoryStore,
+        user: ChatId,
+        message: Option<Message>,
+        page: usize,
+    ) -> Result<Option<String>, ErrorHandler> {
+        let UserSettings { lang, .. } = profiles.get(dialogue.chat_id()).await?;
+        let (message, mut pages) = match (message, dialogue.get().await?) {
+            (Some(message), Some(State::Paging(pages))) if pages.message_id == message.id => {
+                (message, pages)
             }
-        } else {
-            let result = vec_to_string(to_send, "\n_________________________\n");
-            CallBackHandler::send_message(&result, bot, dialogue).await?;
+            _ => return Ok(Some(lang.fail_messages.expired.clone())),
+        };
+        if page == pages.current || page >= pages.len() {
+            return Ok(None);
+        }
+        pages.current = page;
+        let (text, keyboard) = pages.render(&lang);
+        let mut request = bot.edit_message_text(dialogue.chat_id(), message.id, text);
+        if let Some(keyboard) = keyboard {
+            request = request.reply_markup(keyboard);
+        }
+        request.await?;
+        Self::show_photos(bot, dialogue, &mut pages).await?;
+        Self::record_viewed(history, user, &pages).await?;
+        dialogue.update(State::Paging(pages)).await?;
+        Ok(None)
+    }
+
+    /// Shows the full recipe in place of the drink on the paged results,
+    /// false when the message is not the current page or the recipe does not fit.
+    pub async fn expand_in_page<T>(
+        bot: &AutoSend<Bot>,
+        dialogue: &LocalDialogue,
+        lang: &Lang,
+        message: &Message,
+        drink: &T,
+    ) -> Result<bool, ErrorHandler>
+    where
+        T: Display + WithPhoto + WithActions,
+    {
+        let mut pages = match dialogue.get().await? {
+            Some(State::Paging(pages)) if pages.message_id == message.id => pages,
+            _ => return Ok(false),
+        };
+        if !pages.expand(PageItem::new(drink, false)) {
+            return Ok(false);
+        }
+        let (text, keyboard) = pages.render(lang);
+        let mut request = bot.edit_message_text(dialogue.chat_id(), message.id, text);
+        if let Some(keyboard) = keyboard {
+            request = request.reply_markup(keyboard);
+        }
+        request.await?;
+        dialogue.update(State::Paging(pages)).await?;
+        Ok(true)
+    }
+
+    /// Everything on the shown page counts as viewed for the recommendations.
+    async fn record_viewed(history: &HistoryStore, user: ChatId, pages: &Pages) -> ReturnTy {
+        for id in pages.page_drinks() {
+            history.record(user, &id, Event::Viewed).await?;
         }
-        CommandsHandler::start_commands(bot, dialogue).await?;
         Ok(())
     }
-    fn get_range(vec_len: usize, settings_params: usize) -> (usize, usize) {
-        match vec_len > settings_params {
-            false => (0_usize, vec_len),
-            _ => {
-                let random_start = random_num_in_range(0, vec_len - settings_params as usize);
-                (random_start, random_start + settings_params)
+
+    /// Replaces the photos of the previous page with the ones of the current page,
+    /// new photo messages are sent only when the page has more of them.
+    async fn show_photos(
+        bot: &AutoSend<Bot>,
+        dialogue: &LocalDialogue,
+        pages: &mut Pages,
+    ) -> ReturnTy {
+        let photos = pages.page_photos();
+        for (index, image) in photos.iter().enumerate() {
+            let file = InputFile::url(Url::parse(image)?);
+            match pages.photos.get(index) {
+                Some(id) => {
+                    bot.edit_message_media(
+                        dialogue.chat_id(),
+                        *id,
+                        InputMedia::Photo(InputMediaPhoto::new(file)),
+                    )
+                    .await?;
+                }
+                None => {
+                    let message = bot.send_photo(dialogue.chat_id(), file).await?;
+                    pages.photos.push(message.id);
+                }
             }
         }
+        for id in pages.photos.split_off(photos.len().min(pages.photos.len())) {
+            bot.delete_message(dialogue.chat_id(), id).await?;
+        }
+        Ok(())
     }
 
     pub async fn find_ingredient_by_name(
         message: Message,
         bot: AutoSend<Bot>,
         dialogue: LocalDialogue,
+        service: Arc<DrinksService>,
+        profiles: Arc<ProfileStore>,
     ) -> ReturnTy {
         if let Some(message) = message.text() {
-            let user_settings = CommandsHandler::get_settings(&dialogue).await?;
-            if let Some(result) =
-                DrinksService::get_ingredient_by_name(message, user_settings.lang.clone()).await?
+            let user_settings = profiles.get(dialogue.chat_id()).await?;
+            if let Some(result) = service
+                .get_ingredient_by_name(message, user_settings.lang.clone())
+                .await?
             {
-                for result in result {
-                    CallBackHandler::send_message(&result.to_string(), &bot, &dialogue).await?;
+                for result in result.iter() {
+                    CallBackHandler::send_message_with_keyboard(
+                        &result.to_string(),
+                        actions_keyboard(std::slice::from_ref(result), &user_settings.lang),
+                        &bot,
+                        &dialogue,
+                    )
+                    .await?;
                 }
 
-                CommandsHandler::start_commands(&bot, &dialogue).await?;
+                CommandsHandler::start_commands(&bot, &dialogue, &profiles).await?;
             } else {
                 Self::send_wrong_message(
                     &user_settings.lang.fail_messages.wrong_ingredient,
                     &bot,
                     &dialogue,
+                    &profiles,
                 )
                 .await?;
             }
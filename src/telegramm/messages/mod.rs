@@ -1 +1,3 @@
+pub mod group;
 pub mod message_handler;
+pub mod pages;
@@ -0,0 +1,304 @@
+use std::fmt::Display;
+
+use serde::{Deserialize, Serialize};
+use teloxide::types::{InlineKeyboardButton, InlineKeyboardMarkup};
+
+use crate::cocktails_api::schemas::drink::WithPhoto;
+use crate::localization::lang::Lang;
+use crate::telegramm::buttons::inline::{actions_keyboard, CallbackData, WithActions};
+use crate::telegramm::messages::message_handler::TELEGRAMM_CHAR_LIMIT;
+
+const SEPARATOR: &str = "\n_________________________\n";
+/// Room left in every page for the separators.
+const PAGE_CHAR_LIMIT: usize = TELEGRAMM_CHAR_LIMIT - 96;
+
+/// Already rendered result, so turning a page does not refetch anything.
+#[derive(Clone, Serialize, Deserialize, Debug)]
+pub struct PageItem {
+    pub title: String,
+    pub text: String,
+    pub actions: Vec<CallbackData>,
+    /// Image link, only kept when the user wants images.
+    #[serde(default)]
+    pub photo: Option<String>,
+    #[serde(default)]
+    pub drink_id: Option<String>,
+}
+
+impl PageItem {
+    pub fn new<T>(result: &T, with_images: bool) -> Self
+    where
+        T: Display + WithPhoto + WithActions,
+    {
+        Self {
+            title: result.title(),
+            text: Pages::truncate(result.to_string()),
+            actions: result.actions(),
+            photo: match with_images {
+                true => result.get_url(),
+                false => None,
+            },
+            drink_id: result.drink_id(),
+        }
+    }
+}
+
+impl WithActions for PageItem {
+    fn title(&self) -> String {
+        self.title.clone()
+    }
+    fn actions(&self) -> Vec<CallbackData> {
+        self.actions.clone()
+    }
+    fn drink_id(&self) -> Option<String> {
+        self.drink_id.clone()
+    }
+}
+
+/// Results split into pages which fit into a single message.
+#[derive(Clone, Serialize, Deserialize, Debug, Default)]
+pub struct Pages {
+    pub message_id: i32,
+    pub current: usize,
+    pub pages: Vec<Vec<PageItem>>,
+    /// Photo messages of the current page, edited in place when turning it.
+    #[serde(default)]
+    pub photos: Vec<i32>,
+}
+
+impl Pages {
+    /// The images are sent as separate photos, so they do not change the page size.
+    pub fn new<T>(results: &[T], per_page: usize, with_images: bool) -> Self
+    where
+        T: Display + WithPhoto + WithActions,
+    {
+        let per_page = per_page.max(1);
+        let mut pages: Vec<Vec<PageItem>> = vec![];
+        let mut size = 0;
+        for result in results.iter() {
+            let item = PageItem::new(result, with_images);
+            match pages.last_mut() {
+                Some(page)
+                    if page.len() < per_page
+                        && size + SEPARATOR.len() + item.text.len() <= PAGE_CHAR_LIMIT =>
+                {
+                    size += SEPARATOR.len() + item.text.len();
+                    page.push(item);
+                }
+                _ => {
+                    size = item.text.len();
+                    pages.push(vec![item]);
+                }
+            }
+        }
+        Self {
+            message_id: 0,
+            current: 0,
+            pages,
+            photos: vec![],
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.pages.len()
+    }
+
+    /// Image links of the current page.
+    pub fn page_photos(&self) -> Vec<String> {
+        self.pages
+            .get(self.current)
+            .map(|items| items.iter().filter_map(|item| item.photo.clone()).collect())
+            .unwrap_or_default()
+    }
+
+    /// Drinks of the current page.
+    pub fn page_drinks(&self) -> Vec<String> {
+        self.pages
+            .get(self.current)
+            .map(|items| items.iter().filter_map(WithActions::drink_id).collect())
+            .unwrap_or_default()
+    }
+
+    /// Puts the item in place of the same drink on the current page, the photo
+    /// stays as it is. Nothing changes when the page would outgrow a message.
+    pub fn expand(&mut self, mut item: PageItem) -> bool {
+        let page = match self.pages.get_mut(self.current) {
+            Some(page) => page,
+            None => return false,
+        };
+        let index = match page
+            .iter()
+            .position(|old| old.drink_id.is_some() && old.drink_id == item.drink_id)
+        {
+            Some(index) => index,
+            None => return false,
+        };
+        let size = page.iter().map(|old| old.text.len()).sum::<usize>() - page[index].text.len()
+            + item.text.len()
+            + SEPARATOR.len() * (page.len() - 1);
+        if size > PAGE_CHAR_LIMIT {
+            return false;
+        }
+        item.photo = page[index].photo.take();
+        page[index] = item;
+        true
+    }
+
+    /// Text and buttons of the current page.
+    pub fn render(&self, lang: &Lang) -> (String, Option<InlineKeyboardMarkup>) {
+        let items = self
+            .pages
+            .get(self.current)
+            .map(Vec::as_slice)
+            .unwrap_or_default();
+        let text = items
+            .iter()
+            .map(|item| item.text.as_str())
+            .collect::<Vec<&str>>()
+            .join(SEPARATOR);
+
+        let mut keyboard = actions_keyboard(items, lang)
+            .map(|markup| markup.inline_keyboard)
+            .unwrap_or_default();
+        if self.len() > 1 {
+            let mut navigation = vec![];
+            if self.current > 0 {
+                navigation.push(InlineKeyboardButton::callback(
+                    lang.buttons.inline.prev.clone(),
+                    CallbackData::Page(self.current - 1).to_string(),
+                ));
+            }
+            navigation.push(InlineKeyboardButton::callback(
+                format!(
+                    "{} {} {} {}",
+                    lang.buttons.inline.page,
+                    self.current + 1,
+                    lang.buttons.inline.of,
+                    self.len()
+                ),
+                CallbackData::Page(self.current).to_string(),
+            ));
+            if self.current + 1 < self.len() {
+                navigation.push(InlineKeyboardButton::callback(
+                    lang.buttons.inline.next.clone(),
+                    CallbackData::Page(self.current + 1).to_string(),
+                ));
+            }
+            keyboard.push(navigation);
+        }
+        match keyboard.is_empty() {
+            true => (text, None),
+            false => (text, Some(InlineKeyboardMarkup::new(keyboard))),
+        }
+    }
+
+    fn truncate(mut text: String) -> String {
+        if text.len() > PAGE_CHAR_LIMIT {
+            let mut end = PAGE_CHAR_LIMIT;
+            while !text.is_char_boundary(end) {
+                end -= 1;
+            }
+            text.truncate(end);
+        }
+        text
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::fmt::{Display, Formatter};
+
+    use crate::cocktails_api::schemas::drink::WithPhoto;
+    use crate::localization::lang::Lang;
+    use crate::telegramm::buttons::inline::{CallbackData, WithActions};
+    use crate::telegramm::messages::pages::{PageItem, Pages, PAGE_CHAR_LIMIT};
+
+    struct Sample(String);
+
+    impl Display for Sample {
+        fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+            write!(f, "{}", self.0)
+        }
+    }
+
+    impl WithPhoto for Sample {
+        fn get_url(&self) -> Option<String> {
+            Some(format!("https://example.com/{}.jpg", self.0.len()))
+        }
+    }
+
+    impl WithActions for Sample {
+        fn title(&self) -> String {
+            self.0.clone()
+        }
+        fn actions(&self) -> Vec<CallbackData> {
+            vec![CallbackData::Details(self.0.len().to_string())]
+        }
+        fn drink_id(&self) -> Option<String> {
+            Some(self.0.len().to_string())
+        }
+    }
+
+    fn results(count: usize, size: usize) -> Vec<Sample> {
+        (0..count).map(|_| Sample("a".repeat(size))).collect()
+    }
+
+    #[test]
+    fn test_split_by_count() {
+        let pages = Pages::new(&results(7, 10), 3, false);
+        let sizes = pages.pages.iter().map(Vec::len).collect::<Vec<usize>>();
+        assert_eq!(sizes, vec![3, 3, 1]);
+    }
+
+    #[test]
+    fn test_split_by_chars() {
+        let pages = Pages::new(&results(3, PAGE_CHAR_LIMIT / 2), 10, false);
+        assert_eq!(pages.len(), 3);
+        let pages = Pages::new(&results(1, PAGE_CHAR_LIMIT * 2), 10, false);
+        assert_eq!(pages.pages[0][0].text.len(), PAGE_CHAR_LIMIT);
+    }
+
+    #[test]
+    fn test_images() {
+        let pages = Pages::new(&results(2, 10), 5, true);
+        assert_eq!(pages.len(), 1);
+        assert!(!pages.pages[0][0].text.contains(".jpg"));
+        assert_eq!(pages.page_photos().len(), 2);
+        assert_eq!(pages.page_photos()[0], "https://example.com/10.jpg");
+        let pages = Pages::new(&results(2, 10), 5, false);
+        assert!(pages.page_photos().is_empty());
+    }
+
+    #[test]
+    fn test_render() {
+        let mut pages = Pages::new(&results(5, 10), 2, false);
+        pages.current = 1;
+        assert_eq!(pages.page_drinks(), vec!["10", "10"]);
+        let (text, keyboard) = pages.render(&Lang::default());
+        assert_eq!(text.matches('a').count(), 20);
+        let keyboard = keyboard.unwrap().inline_keyboard;
+        assert_eq!(keyboard.len(), 3);
+        assert_eq!(keyboard[2].len(), 3);
+        assert_eq!(keyboard[2][1].text, "Page 2 of 3");
+    }
+
+    #[test]
+    fn test_expand() {
+        let mut pages = Pages::new(
+            &[Sample("ab".to_string()), Sample("c".to_string())],
+            2,
+            true,
+        );
+        assert!(pages.expand(PageItem::new(&Sample("xy".to_string()), false)));
+        assert_eq!(pages.pages[0][0].text, "xy");
+        assert_eq!(pages.pages[0][1].text, "c");
+        assert_eq!(pages.page_photos()[0], "https://example.com/2.jpg");
+        assert!(!pages.expand(PageItem::new(&Sample("xyz".to_string()), false)));
+        let long = Sample(format!("z{}", "z".repeat(PAGE_CHAR_LIMIT)));
+        assert!(!pages.expand(PageItem {
+            drink_id: Some("1".to_string()),
+            ..PageItem::new(&long, false)
+        }));
+        assert_eq!(pages.pages[0][1].text, "c");
+    }
+}
@@ -7,6 +7,7 @@ use crate::telegramm::state::State;
 pub mod buttons;
 pub mod commands;
 pub mod messages;
+pub mod queries;
 pub mod settings;
 pub mod state;
 
@@ -0,0 +1,129 @@
+use std::ops::Range;
+use std::sync::Arc;
+
+use teloxide::payloads::AnswerInlineQuerySetters;
+use teloxide::prelude::{AutoSend, InlineQuery, Requester};
+use teloxide::types::{
+    ChatId, InlineQueryResult, InlineQueryResultArticle, InlineQueryResultPhoto,
+    InputMessageContent, InputMessageContentText,
+};
+use teloxide::Bot;
+use url::Url;
+
+use crate::cocktails_api::schemas::drink::LangDrink;
+use crate::cocktails_api::schemas::measure::Units;
+use crate::cocktails_api::services::coctail_service::DrinksService;
+use crate::localization::lang::Lang;
+use crate::storage::profiles::ProfileStore;
+use crate::telegramm::ReturnTy;
+
+/// Telegram accepts at most 50 results per answer.
+const RESULTS_PER_ANSWER: usize = 20;
+const CACHE_TIME: u32 = 300;
+const CAPTION_LIMIT: usize = 1024;
+
+pub struct InlineQueryHandler;
+
+impl InlineQueryHandler {
+    pub async fn inline_query(
+        bot: AutoSend<Bot>,
+        query: InlineQuery,
+        service: Arc<DrinksService>,
+        profiles: Arc<ProfileStore>,
+    ) -> ReturnTy {
+        let name = query.query.trim();
+        if name.is_empty() {
+            bot.answer_inline_query(query.id, vec![]).await?;
+            return Ok(());
+        }
+        let (lang, units) = match profiles.find(ChatId(query.from.id.0 as i64)).await? {
+            Some(settings) => (settings.lang, settings.units),
+            None => (
+                Lang::from_code(query.from.language_code.as_deref()),
+                Units::default(),
+            ),
+        };
+        let drinks = service
+            .get_drink_by_name(name, lang, units)
+            .await?
+            .unwrap_or_default();
+        let (range, next_offset) = Self::page(drinks.len(), &query.offset);
+        let results = drinks[range]
+            .iter()
+            .enumerate()
+            .map(|(num, drink)| Self::to_result(drink, num))
+            .collect::<Vec<InlineQueryResult>>();
+
+        bot.answer_inline_query(query.id, results)
+            .cache_time(CACHE_TIME)
+            .is_personal(true)
+            .next_offset(next_offset)
+            .await?;
+        Ok(())
+    }
+
+    /// The offset is the number of results sent before, an empty next offset ends paging.
+    fn page(len: usize, offset: &str) -> (Range<usize>, String) {
+        let start = offset.parse::<usize>().unwrap_or(0).min(len);
+        let end = (start + RESULTS_PER_ANSWER).min(len);
+        let next_offset = match end < len {
+            true => end.to_string(),
+            false => String::new(),
+        };
+        (start..end, next_offset)
+    }
+
+    fn to_result(drink: &LangDrink, num: usize) -> InlineQueryResult {
+        let text = drink.to_string();
+        let id = drink.drink.id.clone().unwrap_or_else(|| num.to_string());
+        let description = drink.drink.category.clone().unwrap_or_default();
+        let image = drink
+            .drink
+            .image
+            .as_deref()
+            .and_then(|image| Url::parse(image).ok());
+        match image {
+            Some(image) if text.len() <= CAPTION_LIMIT => {
+                let thumb = Self::thumb(&image);
+                InlineQueryResultPhoto::new(id, image, thumb)
+                    .title(drink.drink.name.clone())
+                    .description(description)
+                    .caption(text)
+                    .into()
+            }
+            image => {
+                let article = InlineQueryResultArticle::new(
+                    id,
+                    drink.drink.name.clone(),
+                    InputMessageContent::Text(InputMessageContentText::new(text)),
+                )
+                .description(description);
+                match image {
+                    Some(image) => article.thumb_url(Self::thumb(&image)).into(),
+                    None => article.into(),
+                }
+            }
+        }
+    }
+
+    /// The cocktail db serves small copies of the images under `/preview`.
+    fn thumb(image: &Url) -> Url {
+        Url::parse(&format!("{}/preview", image)).unwrap_or_else(|_| image.clone())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::telegramm::queries::inline_query::{InlineQueryHandler, RESULTS_PER_ANSWER};
+
+    #[test]
+    fn test_page() {
+        assert_eq!(
+            InlineQueryHandler::page(45, ""),
+            (0..RESULTS_PER_ANSWER, RESULTS_PER_ANSWER.to_string())
+        );
+        assert_eq!(InlineQueryHandler::page(45, "40"), (40..45, String::new()));
+        assert_eq!(InlineQueryHandler::page(5, "wrong"), (0..5, String::new()));
+        assert_eq!(InlineQueryHandler::page(5, "10"), (5..5, String::new()));
+    }
+}
@@ -0,0 +1 @@
+pub mod inline_query;
@@ -1,5 +1,6 @@
 use std::fmt::{Display, Formatter};
 
+use crate::cocktails_api::schemas::measure::Units;
 use crate::error::error_handler::{ErrorHandler, ErrorType};
 use crate::localization::lang::Lang;
 use crate::utils::str_builder::StringBuilder;
@@ -12,6 +13,7 @@ pub enum SettingsKeyboard {
     Images,
     MessageLimit,
     Lang,
+    Units,
     Back,
 }
 
@@ -37,6 +39,22 @@ pub struct UserSettings {
     pub send_image: bool,
     pub limit_of_messages: u32,
     pub lang: Lang,
+    /// Ingredients the user has at home.
+    #[serde(default)]
+    pub bar: Vec<String>,
+    #[serde(default)]
+    pub favorites: Vec<Favorite>,
+    #[serde(default)]
+    pub units: Units,
+    /// Drinks picked for the shopping list.
+    #[serde(default)]
+    pub shopping: Vec<Favorite>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
+pub struct Favorite {
+    pub id: String,
+    pub name: String,
 }
 
 impl Display for UserSettings {
@@ -55,15 +73,20 @@ impl Display for UserSettings {
             )
             .add(
                 &self.lang.settings_descriptions.lang,
-                Some(match self.lang {
-                    Lang::Ukr => self.lang.settings_descriptions.lang_ukr.clone(),
-                    Lang::Eng => self.lang.settings_descriptions.lang_eng.clone(),
-                }),
+                Some(self.lang.name.clone()),
             )
             .add(
                 &self.lang.settings_descriptions.limit,
                 Some(self.limit_of_messages.to_string()),
             )
+            .add(
+                &self.lang.settings_descriptions.units,
+                Some(match self.units {
+                    Units::Original => self.lang.settings_descriptions.units_original.clone(),
+                    Units::Metric => self.lang.settings_descriptions.units_metric.clone(),
+                    Units::Imperial => self.lang.settings_descriptions.units_imperial.clone(),
+                }),
+            )
             .get_str();
         write!(f, "{}", result)
     }
@@ -75,7 +98,11 @@ impl Default for UserSettings {
             name: Some("Dear".to_string()),
             send_image: true,
             limit_of_messages: 10,
-            lang: Lang::Ukr,
+            lang: Lang::default(),
+            bar: vec![],
+            favorites: vec![],
+            units: Units::default(),
+            shopping: vec![],
         }
     }
 }
@@ -1,6 +1,4 @@
 use std::fmt::Display;
-use std::fs::OpenOptions;
-use std::io::prelude::*;
 
 use rand::distributions::Uniform;
 use rand::prelude::Distribution;
@@ -48,20 +46,3 @@ pub fn split(message: &str) -> Vec<String> {
     }
     vec
 }
-
-pub fn write_to_file(text: &str) -> Result<(), ErrorHandler> {
-    let mut file = OpenOptions::new()
-        .write(true)
-        .append(true)
-        .open("./suggestion_bags/suggestion.txt")
-        .map_err(|err| ErrorHandler {
-            msg: err.to_string(),
-            ty: ErrorType::File,
-        })?;
-
-    writeln!(file, "{}\n_____________________________\n", text).map_err(|err| ErrorHandler {
-        msg: err.to_string(),
-        ty: ErrorType::File,
-    })?;
-    Ok(())
-}
@@ -1,3 +1,5 @@
 pub mod helpers;
 pub mod str_builder;
+#[cfg(test)]
+pub mod testing;
 pub mod unicod;
@@ -0,0 +1,72 @@
+use std::collections::BTreeMap;
+
+use sqlx::sqlite::{SqlitePool, SqlitePoolOptions};
+
+use crate::cocktails_api::schemas::drink::Drink;
+
+/// Alcoholic drink without anything but the name, the tests set what they check.
+pub struct DrinkBuilder(Drink);
+
+impl DrinkBuilder {
+    pub fn new(name: &str) -> Self {
+        Self(Drink {
+            id: None,
+            name: name.to_string(),
+            ty: None,
+            category: None,
+            alco: true,
+            glass: None,
+            instructions: None,
+            localized_instructions: BTreeMap::new(),
+            image: None,
+            ingredients: vec![],
+        })
+    }
+
+    pub fn id(mut self, id: &str) -> Self {
+        self.0.id = Some(id.to_string());
+        self
+    }
+
+    pub fn alco(mut self, alco: bool) -> Self {
+        self.0.alco = alco;
+        self
+    }
+
+    pub fn category(mut self, category: &str) -> Self {
+        self.0.category = Some(category.to_string());
+        self
+    }
+
+    pub fn glass(mut self, glass: &str) -> Self {
+        self.0.glass = Some(glass.to_string());
+        self
+    }
+
+    /// Ingredients without measures.
+    pub fn ingredients(mut self, names: &[&str]) -> Self {
+        self.0.ingredients = names.iter().map(|name| (name.to_string(), None)).collect();
+        self
+    }
+
+    pub fn measures(mut self, ingredients: &[(&str, Option<&str>)]) -> Self {
+        self.0.ingredients = ingredients
+            .iter()
+            .map(|(name, measure)| (name.to_string(), measure.map(str::to_string)))
+            .collect();
+        self
+    }
+
+    pub fn build(self) -> Drink {
+        self.0
+    }
+}
+
+/// In-memory database, a single connection keeps it alive for the whole test.
+pub async fn test_pool() -> SqlitePool {
+    SqlitePoolOptions::new()
+        .max_connections(1)
+        .connect("sqlite::memory:")
+        .await
+        .unwrap()
+}